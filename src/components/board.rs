@@ -1,5 +1,7 @@
 use bevy::prelude::*;
 
+use crate::components::tile::TileSuit;
+
 // UI area marker components
 #[derive(Component)]
 pub struct JokerArea;
@@ -26,10 +28,53 @@ pub struct PlayButton;
 #[derive(Component)]
 pub struct DiscardButton;
 
+#[derive(Component)]
+pub struct RiichiButton;
+
+#[derive(Component)]
+pub struct SpeedButton;
+
 #[derive(Component)]
 pub struct StartButton;
 
+#[derive(Component)]
+pub struct EndlessButton;
+
+#[derive(Component)]
+pub struct LeaveShopButton;
+
+/// Shop button buying the pattern card at `index` in `ShopOffers`
+#[derive(Component)]
+pub struct PatternCardButton {
+    pub index: usize,
+}
+
+/// Shop button buying the joker at `index` in `ShopOffers`
+#[derive(Component)]
+pub struct JokerCardButton {
+    pub index: usize,
+}
+
+/// Shop button buying 悔牌 +1
+#[derive(Component)]
+pub struct UndoPerkButton;
+
+/// Shop button selling the joker in `slot` of `Jokers`
+#[derive(Component)]
+pub struct SellJokerButton {
+    pub slot: usize,
+}
+
+#[derive(Component)]
+pub struct StakePrevButton;
+
+#[derive(Component)]
+pub struct StakeNextButton;
+
 // Text display markers
+#[derive(Component)]
+pub struct JokerAreaText;
+
 #[derive(Component)]
 pub struct BaseScoreText;
 
@@ -56,3 +101,64 @@ pub struct SubRoundText;
 
 #[derive(Component)]
 pub struct HandPatternText;
+
+/// What the current selection would score if played
+#[derive(Component)]
+pub struct PreviewText;
+
+#[derive(Component)]
+pub struct MoneyText;
+
+#[derive(Component)]
+pub struct WindText;
+
+#[derive(Component)]
+pub struct ShopMoneyText;
+
+#[derive(Component)]
+pub struct StakeText;
+
+#[derive(Component)]
+pub struct StakePenaltyText;
+
+#[derive(Component)]
+pub struct PlayModeButton;
+
+#[derive(Component)]
+pub struct PlayModeText;
+
+#[derive(Component)]
+pub struct RulesetButton;
+
+#[derive(Component)]
+pub struct RulesetText;
+
+#[derive(Component)]
+pub struct RunLengthButton;
+
+#[derive(Component)]
+pub struct RunLengthText;
+
+/// The play/discard button row, swapped out while a Sichuan blind awaits 定缺
+#[derive(Component)]
+pub struct GameButtonRow;
+
+/// 换三张 and 定缺 buttons shown at the start of a Sichuan blind
+#[derive(Component)]
+pub struct SichuanButtonRow;
+
+#[derive(Component)]
+pub struct ExchangeButton;
+
+#[derive(Component)]
+pub struct VoidSuitButton(pub TileSuit);
+
+#[derive(Component)]
+pub struct SortButton;
+
+#[derive(Component)]
+pub struct UndoButton;
+
+/// World-space label under a meld on the board while a hand is scored
+#[derive(Component)]
+pub struct MeldLabel;
@@ -7,6 +7,22 @@ pub enum TileSuit {
     Sou,    // 条
     Wind,   // 风
     Dragon, // 箭 (中发白)
+    Flower, // 花 (梅兰竹菊), bonus
+    Season, // 季 (春夏秋冬), bonus
+}
+
+impl TileSuit {
+    pub fn label(&self) -> &'static str {
+        match self {
+            TileSuit::Man => "万",
+            TileSuit::Pin => "筒",
+            TileSuit::Sou => "条",
+            TileSuit::Wind => "风",
+            TileSuit::Dragon => "箭",
+            TileSuit::Flower => "花",
+            TileSuit::Season => "季",
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -34,6 +50,21 @@ impl TileId {
                 3 => "白".into(),
                 _ => unreachable!(),
             },
+            TileSuit::Flower => ["梅", "兰", "竹", "菊"][(self.value - 1) as usize].into(),
+            TileSuit::Season => ["春", "夏", "秋", "冬"][(self.value - 1) as usize].into(),
+        }
+    }
+
+    /// Latin-only label, e.g. "5p" or "Rd", for text in the default font
+    pub fn short_label(&self) -> String {
+        match self.suit {
+            TileSuit::Man => format!("{}m", self.value),
+            TileSuit::Pin => format!("{}p", self.value),
+            TileSuit::Sou => format!("{}s", self.value),
+            TileSuit::Wind => ["E", "S", "W", "N"][(self.value - 1) as usize].into(),
+            TileSuit::Dragon => ["Rd", "Gr", "Wh"][(self.value - 1) as usize].into(),
+            TileSuit::Flower => format!("F{}", self.value),
+            TileSuit::Season => ["Sp", "Su", "Au", "Wi"][(self.value - 1) as usize].into(),
         }
     }
 
@@ -49,6 +80,8 @@ impl TileId {
                 3 => Color::srgb(0.5, 0.5, 0.6), // 白 = gray-white
                 _ => unreachable!(),
             },
+            TileSuit::Flower => Color::srgb(0.8, 0.3, 0.6), // pink
+            TileSuit::Season => Color::srgb(0.85, 0.55, 0.1), // orange
         }
     }
 
@@ -56,15 +89,31 @@ impl TileId {
         matches!(self.suit, TileSuit::Wind | TileSuit::Dragon)
     }
 
+    /// Flowers and seasons: revealed when drawn, never part of a hand
+    pub fn is_bonus(&self) -> bool {
+        matches!(self.suit, TileSuit::Flower | TileSuit::Season)
+    }
+
     pub fn is_simple(&self) -> bool {
-        !self.is_honor() && self.value >= 2 && self.value <= 8
+        !self.is_honor() && !self.is_bonus() && self.value >= 2 && self.value <= 8
     }
 
     pub fn is_terminal(&self) -> bool {
-        !self.is_honor() && (self.value == 1 || self.value == 9)
+        !self.is_honor() && !self.is_bonus() && (self.value == 1 || self.value == 9)
+    }
+
+    /// Chips this tile adds to the base when scored:
+    /// simples are worth their face value, terminals and honors 10
+    pub fn chips(&self) -> u32 {
+        if self.is_simple() {
+            self.value as u32
+        } else {
+            10
+        }
     }
 
-    /// Convert to index in [u8; 34] counting array
+    /// Convert to index in [u8; 34] counting array.
+    /// Bonus tiles follow at 34..42, past the end of the counting array.
     pub fn to_index(&self) -> usize {
         match self.suit {
             TileSuit::Man => (self.value - 1) as usize,
@@ -72,10 +121,12 @@ impl TileId {
             TileSuit::Sou => 18 + (self.value - 1) as usize,
             TileSuit::Wind => 27 + (self.value - 1) as usize,
             TileSuit::Dragon => 31 + (self.value - 1) as usize,
+            TileSuit::Flower => 34 + (self.value - 1) as usize,
+            TileSuit::Season => 38 + (self.value - 1) as usize,
         }
     }
 
-    /// Convert from index in [u8; 34] counting array
+    /// Convert from index in [u8; 34] counting array (or 34..42 for bonus tiles)
     pub fn from_index(index: usize) -> Self {
         match index {
             0..=8 => TileId { suit: TileSuit::Man, value: (index + 1) as u8 },
@@ -83,6 +134,8 @@ impl TileId {
             18..=26 => TileId { suit: TileSuit::Sou, value: (index - 17) as u8 },
             27..=30 => TileId { suit: TileSuit::Wind, value: (index - 26) as u8 },
             31..=33 => TileId { suit: TileSuit::Dragon, value: (index - 30) as u8 },
+            34..=37 => TileId { suit: TileSuit::Flower, value: (index - 33) as u8 },
+            38..=41 => TileId { suit: TileSuit::Season, value: (index - 37) as u8 },
             _ => unreachable!(),
         }
     }
@@ -140,6 +193,17 @@ impl Tile {
         }
         tiles
     }
+
+    /// 花/季: one of each flower and season
+    pub fn generate_bonus_set() -> Vec<Tile> {
+        [TileSuit::Flower, TileSuit::Season]
+            .into_iter()
+            .flat_map(|suit| (1..=4).map(move |value| Tile {
+                id: TileId { suit, value },
+                copy_index: 0,
+            }))
+            .collect()
+    }
 }
 
 #[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Default)]
@@ -148,8 +212,153 @@ pub enum TileLocation {
     Wall,
     Hand,
     Board,
+    Bonus,
     Discarded,
 }
 
+impl TileLocation {
+    pub fn label(&self) -> &'static str {
+        match self {
+            TileLocation::Wall => "牌山",
+            TileLocation::Hand => "手牌",
+            TileLocation::Board => "出牌区",
+            TileLocation::Bonus => "花牌",
+            TileLocation::Discarded => "弃牌",
+        }
+    }
+}
+
 #[derive(Component, Debug)]
 pub struct TileSelected;
+
+/// Picked up by the mouse; follows the cursor instead of its hand slot
+#[derive(Component, Debug)]
+pub struct Dragging;
+
+/// Under the mouse pointer
+#[derive(Component, Debug)]
+pub struct TileHovered;
+
+/// Flipping away after a discard; hidden once `elapsed` runs out
+#[derive(Component, Debug, Default)]
+pub struct Discarding {
+    pub elapsed: f32,
+}
+
+/// Eases a tile toward the resting spot the layout gives it
+#[derive(Component, Debug, Clone, Default)]
+pub struct TileTween {
+    /// Resting spot, written by the layout every frame
+    pub home: Vec3,
+    /// Start and end of the current slide
+    pub from: Vec3,
+    pub to: Vec3,
+    pub elapsed: f32,
+    pub duration: f32,
+    /// Peak height of the slide above a straight line
+    pub arc: f32,
+    /// Location as of last frame, to tell where a new slide comes from
+    pub location: TileLocation,
+}
+
+impl TileTween {
+    pub fn start(&mut self, from: Vec3, duration: f32, arc: f32) {
+        self.from = from;
+        self.to = self.home;
+        self.elapsed = 0.0;
+        self.duration = duration;
+        self.arc = arc;
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.elapsed >= self.duration
+    }
+
+    /// Position along the slide, easing out, lifted along a parabola by `arc`
+    pub fn position(&self) -> Vec3 {
+        if self.is_done() {
+            return self.to;
+        }
+        let t = self.elapsed / self.duration;
+        let eased = 1.0 - (1.0 - t).powi(3);
+        self.from.lerp(self.to, eased) + Vec3::Y * self.arc * 4.0 * t * (1.0 - t)
+    }
+}
+
+/// Which play of the sub-round (1-based) put this tile on the board
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PlayedOn(pub u32);
+
+/// Special upgrade carried by a tile, on top of its face value
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TileEnhancement {
+    Bonus, // 金牌: extra chips
+    Mult,  // 红利: +mult
+    Glass, // 琉璃: ×mult
+    Echo,  // 回响: scores one extra time
+}
+
+impl TileEnhancement {
+    pub const ALL: [TileEnhancement; 4] = [
+        TileEnhancement::Bonus,
+        TileEnhancement::Mult,
+        TileEnhancement::Glass,
+        TileEnhancement::Echo,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            TileEnhancement::Bonus => "金牌",
+            TileEnhancement::Mult => "红利",
+            TileEnhancement::Glass => "琉璃",
+            TileEnhancement::Echo => "回响",
+        }
+    }
+
+    /// What the enhancement adds when the tile is scored
+    pub fn description(&self) -> String {
+        match self {
+            TileEnhancement::Bonus => format!("+{} 底注", self.extra_chips()),
+            TileEnhancement::Mult => format!("+{} 倍率", self.extra_mult()),
+            TileEnhancement::Glass => format!("×{} 倍率", self.x_mult()),
+            TileEnhancement::Echo => format!("额外计分 {} 次", self.retriggers()),
+        }
+    }
+
+    pub fn extra_chips(&self) -> u32 {
+        match self {
+            TileEnhancement::Bonus => 30,
+            _ => 0,
+        }
+    }
+
+    pub fn extra_mult(&self) -> u32 {
+        match self {
+            TileEnhancement::Mult => 4,
+            _ => 0,
+        }
+    }
+
+    pub fn x_mult(&self) -> u32 {
+        match self {
+            TileEnhancement::Glass => 2,
+            _ => 1,
+        }
+    }
+
+    pub fn retriggers(&self) -> u32 {
+        match self {
+            TileEnhancement::Echo => 1,
+            _ => 0,
+        }
+    }
+
+    pub fn tint(&self) -> Color {
+        match self {
+            TileEnhancement::Bonus => Color::srgb(1.0, 0.88, 0.5),
+            TileEnhancement::Mult => Color::srgb(1.0, 0.7, 0.7),
+            TileEnhancement::Glass => Color::srgb(0.75, 0.9, 1.0),
+            TileEnhancement::Echo => Color::srgb(0.85, 0.75, 1.0),
+        }
+    }
+}
@@ -1,22 +1,69 @@
 use bevy::prelude::*;
 
+use crate::components::tile::TileSuit;
+use crate::plugins::scoring::{HandResult, ScoreEffect};
+use crate::score::Score;
+
 #[derive(Event, Clone)]
 pub struct TileClickedEvent {
     pub tile_entity: Entity,
 }
 
+/// Right click on a tile: show what it is and where it sits
+#[derive(Event, Clone)]
+pub struct InspectTileEvent {
+    pub tile_entity: Entity,
+}
+
 #[derive(Event, Clone)]
 pub struct PlayTilesEvent;
 
 #[derive(Event, Clone)]
 pub struct DiscardTilesEvent;
 
+/// Take back the last play or discard
+#[derive(Event, Clone)]
+pub struct UndoEvent;
+
+/// Declare riichi (立直), locking the hand for the rest of the sub-round
+#[derive(Event, Clone)]
+pub struct DeclareRiichiEvent;
+
+/// 换三张: swap three selected tiles of one suit back into the wall
+#[derive(Event, Clone)]
+pub struct ExchangeTilesEvent;
+
+/// 定缺: declare the suit that may not appear in the scored board
+#[derive(Event, Clone)]
+pub struct DeclareVoidEvent {
+    pub suit: TileSuit,
+}
+
+/// Abandon the run for the menu; needs pressing twice to go through
+#[derive(Event, Clone)]
+pub struct QuitToMenuEvent;
+
+/// A play was refused, e.g. it didn't declare a valid meld
+#[derive(Event, Clone)]
+pub struct PlayRejectedEvent {
+    pub reason: &'static str,
+}
+
+/// One step of the score breakdown as it plays back, and the tile it came from
+#[derive(Event, Clone)]
+pub struct ScoreStepEvent {
+    pub effect: ScoreEffect,
+    pub tile: Option<Entity>,
+}
+
 #[derive(Event, Clone)]
 pub struct ScoreCalculatedEvent {
-    pub base: u32,
-    pub multiplier: u32,
-    pub total: u32,
+    pub base: Score,
+    pub multiplier: Score,
+    pub total: Score,
     pub pattern_name: String,
+    /// The pattern and its level, before tiles and modifiers added to it
+    pub hand: HandResult,
 }
 
 #[derive(Event, Clone)]
@@ -26,3 +73,25 @@ pub struct RoundEndedEvent {
 
 #[derive(Event, Clone)]
 pub struct StartGameEvent;
+
+#[derive(Event, Clone)]
+pub struct BuyPatternCardEvent {
+    pub index: usize,
+}
+
+#[derive(Event, Clone)]
+pub struct BuyJokerEvent {
+    pub index: usize,
+}
+
+/// Sell the joker in `slot`; eternal jokers stay put
+#[derive(Event, Clone)]
+pub struct SellJokerEvent {
+    pub slot: usize,
+}
+
+#[derive(Event, Clone)]
+pub struct BuyUndoPerkEvent;
+
+#[derive(Event, Clone)]
+pub struct LeaveShopEvent;
@@ -4,6 +4,7 @@ mod components;
 mod events;
 mod plugins;
 mod resources;
+mod score;
 
 fn main() {
     App::new()
@@ -1,22 +1,134 @@
+use bevy::ecs::system::SystemParam;
 use bevy::prelude::*;
+use rand::{thread_rng, Rng};
 
 use crate::components::tile::*;
 use crate::events::*;
 use crate::plugins::game::{AppState, PlayPhase};
+use crate::plugins::scoring::{can_reach_tenpai, declare_meld, Meld, MeldKind};
 use crate::resources::*;
 
+/// Plays and discards that can be taken back in each sub-round
+pub const DEFAULT_UNDO_LIMIT: u32 = 2;
+
 pub struct BoardPlugin;
 
 impl Plugin for BoardPlugin {
     fn build(&self, app: &mut App) {
-        app.add_observer(on_play_tiles)
+        app.init_resource::<PlayMode>()
+            .init_resource::<ActionHistory>()
+            .add_observer(on_play_tiles)
             .add_observer(on_discard_tiles)
+            .add_observer(on_undo)
+            .add_observer(on_declare_riichi)
+            .add_observer(on_exchange_tiles)
+            .add_observer(on_declare_void)
             .add_systems(
                 Update,
-                check_phase_transition
-                    .run_if(in_state(AppState::Playing))
-                    .run_if(in_state(PlayPhase::Selecting)),
-            );
+                (
+                    reveal_bonus_tiles.run_if(in_state(AppState::Playing)),
+                    check_phase_transition
+                        .run_if(in_state(AppState::Playing))
+                        .run_if(in_state(PlayPhase::Selecting)),
+                )
+                    .chain(),
+            )
+            .add_systems(OnExit(PlayPhase::Selecting), reset_history);
+    }
+}
+
+/// The hand, board and wall, which plays, discards and undos all rearrange
+#[derive(SystemParam)]
+struct BoardTables<'w> {
+    hand: ResMut<'w, PlayerHand>,
+    board: ResMut<'w, PlayBoard>,
+    wall: ResMut<'w, TileWall>,
+}
+
+/// The tables and counters as they were before a play or discard
+#[derive(Debug, Clone)]
+struct Snapshot {
+    hand: Vec<Entity>,
+    board: Vec<Entity>,
+    melds: Vec<DeclaredMeld>,
+    bonus: Vec<Entity>,
+    wall: Vec<Entity>,
+    last_tile: Option<Entity>,
+    plays_remaining: u32,
+    discards_remaining: u32,
+    plays_made: u32,
+    discards_made: u32,
+    riichi: Option<u32>,
+    /// Tiles the action took from the hand, selected again on undo
+    moved: Vec<Entity>,
+}
+
+impl BoardTables<'_> {
+    fn snapshot(&self, game_state: &GameState, moved: &[Entity]) -> Snapshot {
+        Snapshot {
+            hand: self.hand.tiles.clone(),
+            board: self.board.tiles.clone(),
+            melds: self.board.melds.clone(),
+            bonus: self.board.bonus.clone(),
+            wall: self.wall.tiles.clone(),
+            last_tile: self.wall.last_tile,
+            plays_remaining: game_state.plays_remaining,
+            discards_remaining: game_state.discards_remaining,
+            plays_made: game_state.plays_made,
+            discards_made: game_state.discards_made,
+            riichi: game_state.riichi,
+            moved: moved.to_vec(),
+        }
+    }
+}
+
+/// Plays and discards of the current sub-round that can still be taken back
+#[derive(Resource, Debug)]
+pub struct ActionHistory {
+    actions: Vec<Snapshot>,
+    /// Undos allowed per sub-round
+    pub limit: u32,
+    pub undos_left: u32,
+}
+
+impl Default for ActionHistory {
+    fn default() -> Self {
+        Self {
+            actions: Vec::new(),
+            limit: DEFAULT_UNDO_LIMIT,
+            undos_left: DEFAULT_UNDO_LIMIT,
+        }
+    }
+}
+
+impl ActionHistory {
+    /// Keep only as many actions as there are undos left
+    fn record(&mut self, snapshot: Snapshot) {
+        self.actions.push(snapshot);
+        let excess = self.actions.len().saturating_sub(self.undos_left as usize);
+        self.actions.drain(..excess);
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.actions.is_empty()
+    }
+
+    /// Make the actions so far final, without spending any undos.
+    /// Declaring riichi does this, so undoing can't lift its discard lock.
+    fn commit(&mut self) {
+        self.actions.clear();
+    }
+
+    /// One more undo per sub-round, starting with this one
+    pub fn raise_limit(&mut self) {
+        self.limit += 1;
+        self.undos_left += 1;
+    }
+
+    /// Forget the sub-round's actions and refill the undos
+    fn reset(&mut self) {
+        self.actions.clear();
+        self.undos_left = self.limit;
     }
 }
 
@@ -24,39 +136,69 @@ impl Plugin for BoardPlugin {
 fn on_play_tiles(
     _trigger: On<PlayTilesEvent>,
     mut commands: Commands,
-    mut hand: ResMut<PlayerHand>,
-    mut board: ResMut<PlayBoard>,
-    mut wall: ResMut<TileWall>,
+    mut tables: BoardTables,
+    mut history: ResMut<ActionHistory>,
     mut game_state: ResMut<GameState>,
-    selected_q: Query<Entity, (With<Tile>, With<TileSelected>)>,
+    tile_q: Query<(&Tile, Has<TileSelected>)>,
 ) {
     if game_state.plays_remaining == 0 {
         return;
     }
+    if game_state.awaiting_void() {
+        commands.trigger(PlayRejectedEvent { reason: "请先定缺" });
+        return;
+    }
 
     // Collect selected tile entities that are in the hand
-    let selected_in_hand: Vec<Entity> = hand
+    let selected_in_hand: Vec<Entity> = tables
+        .hand
         .tiles
         .iter()
         .copied()
-        .filter(|e| selected_q.contains(*e))
+        .filter(|e| tile_q.get(*e).is_ok_and(|(_, selected)| selected))
         .collect();
 
     if selected_in_hand.is_empty() {
         return;
     }
+    let snapshot = tables.snapshot(&game_state, &selected_in_hand);
+    let BoardTables { hand, board, wall } = &mut tables;
+
+    if game_state.play_mode == PlayMode::Declared {
+        let ids: Vec<TileId> = selected_in_hand
+            .iter()
+            .filter_map(|&e| tile_q.get(e).ok().map(|(t, _)| t.id))
+            .collect();
+        match check_declared_play(&ids, board) {
+            Ok(meld) => board.melds.push(DeclaredMeld {
+                meld,
+                tiles: selected_in_hand.clone(),
+            }),
+            Err(reason) => {
+                commands.trigger(PlayRejectedEvent { reason });
+                return;
+            }
+        }
+    }
+
+    game_state.plays_made += 1;
+    let play = game_state.plays_made;
 
     // Move tiles from hand to board
     for &entity in &selected_in_hand {
         hand.tiles.retain(|e| *e != entity);
         board.tiles.push(entity);
-        commands.entity(entity).remove::<TileSelected>();
+        commands
+            .entity(entity)
+            .remove::<TileSelected>()
+            .insert(PlayedOn(play));
     }
+    history.record(snapshot);
 
     // Draw replacement tiles from wall
     let draw_count = selected_in_hand.len().min(wall.tiles.len());
     for _ in 0..draw_count {
-        if let Some(entity) = wall.tiles.pop() {
+        if let Some(entity) = wall.draw() {
             hand.tiles.push(entity);
         }
     }
@@ -64,20 +206,46 @@ fn on_play_tiles(
     game_state.plays_remaining = game_state.plays_remaining.saturating_sub(1);
 }
 
+/// The meld a declared play makes, or why it can't be played.
+/// A hand holds at most four melds and one pair.
+pub fn check_declared_play(ids: &[TileId], board: &PlayBoard) -> Result<Meld, &'static str> {
+    let meld = declare_meld(ids).ok_or("须为顺子、刻子、杠子或雀头")?;
+    let pairs = board.melds.iter().filter(|d| d.meld.kind == MeldKind::Pair).count();
+    if meld.kind == MeldKind::Pair {
+        if pairs > 0 {
+            return Err("已有雀头");
+        }
+    } else if board.melds.len() - pairs >= 4 {
+        return Err("已有四组面子");
+    }
+    Ok(meld)
+}
+
 /// Observer: when DiscardTilesEvent is triggered, discard selected tiles from hand
 fn on_discard_tiles(
     _trigger: On<DiscardTilesEvent>,
     mut commands: Commands,
-    mut hand: ResMut<PlayerHand>,
-    mut wall: ResMut<TileWall>,
+    mut tables: BoardTables,
+    mut history: ResMut<ActionHistory>,
     mut game_state: ResMut<GameState>,
     selected_q: Query<Entity, (With<Tile>, With<TileSelected>)>,
 ) {
     if game_state.discards_remaining == 0 {
         return;
     }
+    if game_state.riichi.is_some() {
+        commands.trigger(PlayRejectedEvent {
+            reason: "立直后不能弃牌",
+        });
+        return;
+    }
+    if game_state.awaiting_void() {
+        commands.trigger(PlayRejectedEvent { reason: "请先定缺" });
+        return;
+    }
 
-    let selected_in_hand: Vec<Entity> = hand
+    let selected_in_hand: Vec<Entity> = tables
+        .hand
         .tiles
         .iter()
         .copied()
@@ -87,34 +255,349 @@ fn on_discard_tiles(
     if selected_in_hand.is_empty() {
         return;
     }
+    history.record(tables.snapshot(&game_state, &selected_in_hand));
+    let BoardTables { hand, wall, .. } = &mut tables;
 
-    // Remove from hand, mark as discarded, hide
+    // Remove from hand, mark as discarded and flip it away
     for &entity in &selected_in_hand {
         hand.tiles.retain(|e| *e != entity);
         commands
             .entity(entity)
             .remove::<TileSelected>()
-            .insert(Visibility::Hidden);
+            .insert((TileLocation::Discarded, Discarding::default()));
     }
 
     // Draw replacement tiles from wall
     let draw_count = selected_in_hand.len().min(wall.tiles.len());
     for _ in 0..draw_count {
-        if let Some(entity) = wall.tiles.pop() {
+        if let Some(entity) = wall.draw() {
             hand.tiles.push(entity);
         }
     }
 
     game_state.discards_remaining = game_state.discards_remaining.saturating_sub(1);
+    game_state.discards_made += 1;
 }
 
-/// Check if the board is full (14 tiles) or plays are exhausted → transition to Scoring
+/// Observer: take back the last play or discard of the sub-round, putting
+/// drawn tiles back on the wall in their old order and the counters as they were
+fn on_undo(
+    _trigger: On<UndoEvent>,
+    mut commands: Commands,
+    mut tables: BoardTables,
+    mut history: ResMut<ActionHistory>,
+    mut game_state: ResMut<GameState>,
+    mut transform_q: Query<&mut Transform, With<Tile>>,
+) {
+    if history.undos_left == 0 {
+        commands.trigger(PlayRejectedEvent { reason: "悔牌次数已用完" });
+        return;
+    }
+    let Some(snapshot) = history.actions.pop() else {
+        commands.trigger(PlayRejectedEvent { reason: "没有可悔的牌" });
+        return;
+    };
+    history.undos_left -= 1;
+
+    // Tiles drawn since, replacements for revealed bonus tiles included
+    for &entity in &snapshot.wall {
+        if !tables.wall.tiles.contains(&entity) {
+            commands
+                .entity(entity)
+                .remove::<TileSelected>()
+                .insert((TileLocation::Wall, Visibility::Hidden));
+        }
+    }
+    for &entity in &snapshot.moved {
+        commands
+            .entity(entity)
+            .remove::<(PlayedOn, Discarding)>()
+            .insert(TileSelected);
+        // Caught mid-flip
+        if let Ok(mut transform) = transform_q.get_mut(entity) {
+            transform.scale = Vec3::ONE;
+        }
+    }
+
+    tables.hand.tiles = snapshot.hand;
+    tables.board.tiles = snapshot.board;
+    tables.board.melds = snapshot.melds;
+    tables.board.bonus = snapshot.bonus;
+    tables.wall.tiles = snapshot.wall;
+    tables.wall.last_tile = snapshot.last_tile;
+    game_state.plays_remaining = snapshot.plays_remaining;
+    game_state.discards_remaining = snapshot.discards_remaining;
+    game_state.plays_made = snapshot.plays_made;
+    game_state.discards_made = snapshot.discards_made;
+    game_state.riichi = snapshot.riichi;
+}
+
+fn reset_history(mut history: ResMut<ActionHistory>) {
+    history.reset();
+}
+
+/// Observer: declare riichi when the board plus hand is one tile from a win.
+/// Locks out discards for the rest of the sub-round, and plays made before
+/// it can no longer be undone.
+fn on_declare_riichi(
+    _trigger: On<DeclareRiichiEvent>,
+    mut commands: Commands,
+    hand: Res<PlayerHand>,
+    board: Res<PlayBoard>,
+    mut history: ResMut<ActionHistory>,
+    mut game_state: ResMut<GameState>,
+    tile_q: Query<&Tile>,
+) {
+    if game_state.riichi.is_some() || game_state.plays_remaining == 0 {
+        return;
+    }
+    if game_state.awaiting_void() {
+        commands.trigger(PlayRejectedEvent { reason: "请先定缺" });
+        return;
+    }
+
+    let ids = |entities: &[Entity]| -> Vec<TileId> {
+        entities
+            .iter()
+            .filter_map(|&e| tile_q.get(e).ok().map(|t| t.id))
+            .collect()
+    };
+    // A declared kong's fourth tile doesn't count toward the hand
+    let mut board_ids = ids(&board.tiles);
+    for declared in board.melds.iter().filter(|d| d.meld.kind == MeldKind::Kong) {
+        if let Some(i) = board_ids.iter().position(|&id| id == declared.meld.first) {
+            board_ids.remove(i);
+        }
+    }
+    let rules = game_state.ruleset.rules_for(game_state.void_suit);
+    if !can_reach_tenpai(&board_ids, &ids(&hand.tiles), &rules) {
+        commands.trigger(PlayRejectedEvent { reason: "未听牌" });
+        return;
+    }
+
+    game_state.riichi = Some(game_state.plays_made);
+    history.commit();
+}
+
+/// Observer: 换三张 — three selected tiles of one suit go back into the wall
+/// at random, replaced by three from the top. Once per Sichuan blind, before 定缺.
+fn on_exchange_tiles(
+    _trigger: On<ExchangeTilesEvent>,
+    mut commands: Commands,
+    mut hand: ResMut<PlayerHand>,
+    mut wall: ResMut<TileWall>,
+    mut game_state: ResMut<GameState>,
+    tile_q: Query<(&Tile, Has<TileSelected>)>,
+) {
+    if !game_state.awaiting_void() || game_state.exchanged {
+        return;
+    }
+
+    let selected: Vec<(Entity, TileSuit)> = hand
+        .tiles
+        .iter()
+        .filter_map(|&e| match tile_q.get(e) {
+            Ok((tile, true)) => Some((e, tile.id.suit)),
+            _ => None,
+        })
+        .collect();
+    if selected.len() != 3 || selected.iter().any(|&(_, suit)| suit != selected[0].1) {
+        commands.trigger(PlayRejectedEvent {
+            reason: "须选同花色三张",
+        });
+        return;
+    }
+
+    for _ in 0..selected.len() {
+        if let Some(entity) = wall.draw() {
+            hand.tiles.push(entity);
+        }
+    }
+    let mut rng = thread_rng();
+    for &(entity, _) in &selected {
+        hand.tiles.retain(|e| *e != entity);
+        let at = rng.gen_range(0..=wall.tiles.len());
+        wall.tiles.insert(at, entity);
+        commands
+            .entity(entity)
+            .remove::<TileSelected>()
+            .insert((TileLocation::Wall, Visibility::Hidden));
+    }
+    game_state.exchanged = true;
+}
+
+/// Observer: 定缺 — declare the blind's void suit, unlocking play and discards
+fn on_declare_void(trigger: On<DeclareVoidEvent>, mut game_state: ResMut<GameState>) {
+    if game_state.awaiting_void() {
+        game_state.void_suit = Some(trigger.event().suit);
+        game_state.exchanged = true;
+    }
+}
+
+/// 补花: move any flower or season drawn into the hand to the bonus row
+/// and draw a replacement, which may itself be a bonus tile
+fn reveal_bonus_tiles(
+    mut commands: Commands,
+    mut hand: ResMut<PlayerHand>,
+    mut board: ResMut<PlayBoard>,
+    mut wall: ResMut<TileWall>,
+    tile_q: Query<&Tile>,
+) {
+    while let Some(i) = hand
+        .tiles
+        .iter()
+        .position(|&e| tile_q.get(e).is_ok_and(|t| t.id.is_bonus()))
+    {
+        let entity = hand.tiles.remove(i);
+        commands.entity(entity).remove::<TileSelected>();
+        board.bonus.push(entity);
+        if let Some(replacement) = wall.draw() {
+            hand.tiles.push(replacement);
+        }
+    }
+}
+
+/// Check if the board is full (14 tiles, kongs counting as 3) or plays are exhausted → transition to Scoring
 fn check_phase_transition(
     board: Res<PlayBoard>,
     game_state: Res<GameState>,
     mut next_phase: ResMut<NextState<PlayPhase>>,
 ) {
-    if board.tiles.len() >= 14 || game_state.plays_remaining == 0 {
+    if board.counted_tiles() >= 14 || game_state.plays_remaining == 0 {
         next_phase.set(PlayPhase::Scoring);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::system::RunSystemOnce;
+
+    fn snapshot(plays_made: u32) -> Snapshot {
+        Snapshot {
+            hand: Vec::new(),
+            board: Vec::new(),
+            melds: Vec::new(),
+            bonus: Vec::new(),
+            wall: Vec::new(),
+            last_tile: None,
+            plays_remaining: 4,
+            discards_remaining: 4,
+            plays_made,
+            discards_made: 0,
+            riichi: None,
+            moved: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_history_trims_to_undos_left() {
+        let mut history = ActionHistory::default();
+        for play in 0..3 {
+            history.record(snapshot(play));
+        }
+        // Only the latest actions that could still be undone are kept
+        let kept: Vec<u32> = history.actions.iter().map(|s| s.plays_made).collect();
+        assert_eq!(kept, vec![1, 2]);
+
+        history.undos_left = 1;
+        history.record(snapshot(3));
+        assert_eq!(history.actions.len(), 1);
+        assert_eq!(history.actions[0].plays_made, 3);
+
+        // Riichi makes them final without spending undos
+        history.commit();
+        assert!(!history.can_undo());
+        assert_eq!(history.undos_left, 1);
+    }
+
+    #[test]
+    fn test_history_reset_refills_undos() {
+        let mut history = ActionHistory {
+            limit: 3,
+            undos_left: 0,
+            ..default()
+        };
+        history.record(snapshot(0));
+        history.reset();
+        assert!(!history.can_undo());
+        assert_eq!(history.undos_left, 3);
+
+        // A raised limit carries into every later sub-round
+        history.raise_limit();
+        history.undos_left = 0;
+        history.reset();
+        assert_eq!(history.undos_left, 4);
+    }
+
+    #[test]
+    fn test_undo_restores_play() {
+        let mut world = World::new();
+        world.init_resource::<ActionHistory>();
+        world.insert_resource(GameState::new(Stake::White));
+        world.add_observer(on_play_tiles);
+        world.add_observer(on_undo);
+
+        let mut spawn = |suit, value| {
+            world
+                .spawn((Tile { id: TileId { suit, value }, copy_index: 0 }, Transform::default()))
+                .id()
+        };
+        let played = [spawn(TileSuit::Man, 1), spawn(TileSuit::Man, 2), spawn(TileSuit::Man, 3)];
+        let kept = spawn(TileSuit::Pin, 5);
+        let board_tile = spawn(TileSuit::Sou, 9);
+        // Drawn from the end: 7条, 梅 (replaced by 8筒 on reveal), 9筒
+        let wall = vec![
+            spawn(TileSuit::Pin, 8),
+            spawn(TileSuit::Pin, 9),
+            spawn(TileSuit::Flower, 1),
+            spawn(TileSuit::Sou, 7),
+        ];
+        for &entity in &played {
+            world.entity_mut(entity).insert(TileSelected);
+        }
+        let hand = vec![played[0], kept, played[1], played[2]];
+        world.insert_resource(PlayerHand { tiles: hand.clone() });
+        world.insert_resource(PlayBoard {
+            tiles: vec![board_tile],
+            ..default()
+        });
+        world.insert_resource(TileWall {
+            tiles: wall.clone(),
+            last_tile: None,
+        });
+
+        world.trigger(PlayTilesEvent);
+        world.run_system_once(reveal_bonus_tiles).unwrap();
+        assert_eq!(world.resource::<PlayBoard>().bonus, vec![wall[2]]);
+        assert!(world.resource::<TileWall>().tiles.is_empty());
+        assert_eq!(world.resource::<TileWall>().last_tile, Some(wall[0]));
+        assert_eq!(world.resource::<GameState>().plays_made, 1);
+
+        world.trigger(UndoEvent);
+        world.flush();
+        assert_eq!(world.resource::<PlayerHand>().tiles, hand);
+        let board = world.resource::<PlayBoard>();
+        assert_eq!(board.tiles, vec![board_tile]);
+        assert!(board.melds.is_empty());
+        assert!(board.bonus.is_empty());
+        assert_eq!(world.resource::<TileWall>().tiles, wall);
+        assert_eq!(world.resource::<TileWall>().last_tile, None);
+
+        let game_state = world.resource::<GameState>();
+        assert_eq!(game_state.plays_remaining, 4);
+        assert_eq!(game_state.plays_made, 0);
+        assert_eq!(game_state.discards_remaining, 4);
+        assert_eq!(game_state.discards_made, 0);
+        assert_eq!(world.resource::<ActionHistory>().undos_left, DEFAULT_UNDO_LIMIT - 1);
+
+        for &entity in &played {
+            let tile = world.entity(entity);
+            assert!(tile.contains::<TileSelected>());
+            assert!(!tile.contains::<PlayedOn>());
+        }
+        for &entity in &wall {
+            assert_eq!(world.get::<TileLocation>(entity), Some(&TileLocation::Wall));
+        }
+    }
+}
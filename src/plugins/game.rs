@@ -1,10 +1,14 @@
 use bevy::prelude::*;
 
+use crate::resources::{Profile, RunLength};
+
 pub struct GamePlugin;
 
 impl Plugin for GamePlugin {
     fn build(&self, app: &mut App) {
-        app.init_state::<AppState>()
+        app.insert_resource(Profile::load())
+            .init_resource::<RunLength>()
+            .init_state::<AppState>()
             .add_sub_state::<PlayPhase>()
             .add_systems(Startup, setup_camera);
     }
@@ -16,6 +20,7 @@ pub enum AppState {
     Menu,
     Playing,
     GameOver,
+    Victory,
 }
 
 #[derive(SubStates, Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
@@ -25,6 +30,7 @@ pub enum PlayPhase {
     Selecting,
     Scoring,
     RoundResult,
+    Shop,
 }
 
 #[derive(Component)]
@@ -32,5 +38,5 @@ pub struct MainCamera;
 
 fn setup_camera(mut commands: Commands) {
     commands.insert_resource(ClearColor(Color::srgb(0.12, 0.12, 0.15)));
-    commands.spawn((Camera2d, MainCamera, Msaa::Off));
+    commands.spawn((Camera2d, MainCamera, UiPickingCamera, Msaa::Off));
 }
@@ -0,0 +1,233 @@
+use bevy::prelude::*;
+use bevy::ui::UiSystems;
+
+use crate::components::tile::*;
+use crate::events::*;
+use crate::plugins::game::{AppState, PlayPhase};
+use crate::plugins::input::{toggle_selected, HandCursor};
+use crate::resources::{PlayerHand, SortMode};
+
+/// Stick deflection that counts as a push in that direction
+const STICK_THRESHOLD: f32 = 0.5;
+
+const FOCUS_OUTLINE_COLOR: Color = Color::srgb(0.95, 0.8, 0.2);
+
+pub struct GamepadPlugin;
+
+impl Plugin for GamepadPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PadInput>()
+            .init_resource::<ButtonFocus>()
+            .add_systems(
+                PreUpdate,
+                (read_gamepads, press_focused_button)
+                    .chain()
+                    .after(UiSystems::Focus),
+            )
+            .add_systems(
+                Update,
+                (
+                    pad_hand_system
+                        .run_if(in_state(AppState::Playing))
+                        .run_if(in_state(PlayPhase::Selecting)),
+                    pad_action_system.run_if(in_state(AppState::Playing)),
+                    navigate_buttons,
+                    outline_focused_button,
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// Gamepad input gathered once per frame from every connected pad
+#[derive(Resource, Default)]
+struct PadInput {
+    /// D-pad or stick step this frame, y pointing up
+    step: IVec2,
+    /// Stick direction last frame, so holding the stick moves only once
+    held: IVec2,
+    just_pressed: Vec<GamepadButton>,
+}
+
+impl PadInput {
+    fn pressed(&self, button: GamepadButton) -> bool {
+        self.just_pressed.contains(&button)
+    }
+}
+
+/// UI button the D-pad cursor is on. During play, `None` means the cursor is on the hand.
+#[derive(Resource, Default)]
+pub struct ButtonFocus {
+    pub button: Option<Entity>,
+    /// Button pressed by the pad last frame, released on the next
+    pressed: Option<Entity>,
+}
+
+fn read_gamepads(gamepads: Query<&Gamepad>, mut pad: ResMut<PadInput>) {
+    let mut step = IVec2::ZERO;
+    let mut stick = IVec2::ZERO;
+    pad.just_pressed.clear();
+    for gamepad in &gamepads {
+        let dpad = [
+            (GamepadButton::DPadLeft, IVec2::NEG_X),
+            (GamepadButton::DPadRight, IVec2::X),
+            (GamepadButton::DPadUp, IVec2::Y),
+            (GamepadButton::DPadDown, IVec2::NEG_Y),
+        ];
+        for (button, dir) in dpad {
+            if gamepad.just_pressed(button) {
+                step += dir;
+            }
+        }
+        let axis = gamepad.left_stick();
+        let push = |v: f32| if v.abs() >= STICK_THRESHOLD { v.signum() as i32 } else { 0 };
+        stick += IVec2::new(push(axis.x), push(axis.y));
+        pad.just_pressed.extend(gamepad.get_just_pressed().copied());
+    }
+    let stick = stick.clamp(IVec2::NEG_ONE, IVec2::ONE);
+    if stick != pad.held {
+        step += stick;
+    }
+    pad.held = stick;
+    pad.step = step.clamp(IVec2::NEG_ONE, IVec2::ONE);
+}
+
+/// A presses the focused button by setting its `Interaction`, so every
+/// existing `Interaction::Pressed` handler reacts as if it were clicked.
+/// Runs right after UI focus so the press is seen this frame.
+fn press_focused_button(
+    pad: Res<PadInput>,
+    mut focus: ResMut<ButtonFocus>,
+    mut interaction_q: Query<&mut Interaction, With<Button>>,
+) {
+    if let Some(entity) = focus.pressed.take() {
+        if let Ok(mut interaction) = interaction_q.get_mut(entity) {
+            interaction.set_if_neq(Interaction::None);
+        }
+    }
+    if !pad.pressed(GamepadButton::South) {
+        return;
+    }
+    let Some(entity) = focus.button else { return };
+    if let Ok(mut interaction) = interaction_q.get_mut(entity) {
+        *interaction = Interaction::Pressed;
+        focus.pressed = Some(entity);
+    }
+}
+
+/// With the cursor on the hand: left/right move it, A toggles the tile
+fn pad_hand_system(
+    pad: Res<PadInput>,
+    focus: Res<ButtonFocus>,
+    hand: Res<PlayerHand>,
+    mut cursor: ResMut<HandCursor>,
+    mut commands: Commands,
+    tile_q: Query<Has<TileSelected>, With<Tile>>,
+) {
+    if focus.button.is_some() {
+        return;
+    }
+    if pad.step.x != 0 {
+        cursor.step(pad.step.x, hand.tiles.len());
+    }
+    if pad.pressed(GamepadButton::South) {
+        let focused = cursor.0.and_then(|i| hand.tiles.get(i)).copied();
+        if let Some(entity) = focused {
+            toggle_selected(&mut commands, entity, tile_q.get(entity).unwrap_or(false));
+        }
+    }
+}
+
+/// X plays, Y discards, Select undoes, Start twice returns to the menu, the shoulders cycle the sort mode
+fn pad_action_system(
+    pad: Res<PadInput>,
+    phase: Res<State<PlayPhase>>,
+    mut sort_mode: ResMut<SortMode>,
+    mut commands: Commands,
+) {
+    if *phase.get() == PlayPhase::Selecting {
+        if pad.pressed(GamepadButton::West) {
+            commands.trigger(PlayTilesEvent);
+        }
+        if pad.pressed(GamepadButton::North) {
+            commands.trigger(DiscardTilesEvent);
+        }
+        if pad.pressed(GamepadButton::Select) {
+            commands.trigger(UndoEvent);
+        }
+    }
+    if pad.pressed(GamepadButton::LeftTrigger) {
+        *sort_mode = sort_mode.prev();
+    }
+    if pad.pressed(GamepadButton::RightTrigger) {
+        *sort_mode = sort_mode.next();
+    }
+    if pad.pressed(GamepadButton::Start) {
+        commands.trigger(QuitToMenuEvent);
+    }
+}
+
+/// Move the cursor between visible buttons in reading order.
+/// While selecting tiles, down leaves the hand for the buttons and up goes back.
+fn navigate_buttons(
+    pad: Res<PadInput>,
+    phase: Option<Res<State<PlayPhase>>>,
+    mut focus: ResMut<ButtonFocus>,
+    button_q: Query<(Entity, &UiGlobalTransform, &ComputedNode, &InheritedVisibility), With<Button>>,
+) {
+    let mut buttons: Vec<(Entity, Vec2)> = button_q
+        .iter()
+        .filter(|(_, _, node, visibility)| visibility.get() && !node.is_empty())
+        .map(|(entity, transform, ..)| (entity, transform.translation))
+        .collect();
+    buttons.sort_by(|a, b| a.1.y.total_cmp(&b.1.y).then(a.1.x.total_cmp(&b.1.x)));
+
+    let current = focus.button.and_then(|e| buttons.iter().position(|b| b.0 == e));
+    if current.is_none() && focus.button.is_some() {
+        // The focused button went away with its screen
+        focus.button = None;
+    }
+    if pad.step == IVec2::ZERO || buttons.is_empty() {
+        return;
+    }
+
+    let on_hand = phase.is_some_and(|p| *p.get() == PlayPhase::Selecting);
+    focus.button = match current {
+        None if on_hand && pad.step.y >= 0 => None,
+        None => Some(buttons[0].0),
+        Some(_) if on_hand && pad.step.y > 0 => None,
+        Some(i) => {
+            let forward = pad.step.x > 0 || pad.step.y < 0;
+            let len = buttons.len();
+            let next = if forward { (i + 1) % len } else { (i + len - 1) % len };
+            Some(buttons[next].0)
+        }
+    };
+}
+
+/// Outline the focused button; others keep a transparent outline
+fn outline_focused_button(
+    focus: Res<ButtonFocus>,
+    mut commands: Commands,
+    mut button_q: Query<(Entity, Option<&mut Outline>), With<Button>>,
+) {
+    if !focus.is_changed() {
+        return;
+    }
+    for (entity, outline) in &mut button_q {
+        let color = if focus.button == Some(entity) {
+            FOCUS_OUTLINE_COLOR
+        } else {
+            Color::NONE
+        };
+        match outline {
+            Some(mut outline) => outline.color = color,
+            None if color != Color::NONE => {
+                commands
+                    .entity(entity)
+                    .insert(Outline::new(Val::Px(3.0), Val::Px(2.0), color));
+            }
+            None => {}
+        }
+    }
+}
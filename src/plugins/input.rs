@@ -1,65 +1,535 @@
+use bevy::picking::pointer::Location;
 use bevy::prelude::*;
+use bevy::ui::picking_backend::UiPickingSettings;
 
 use crate::components::tile::*;
-use crate::plugins::game::AppState;
-use crate::plugins::tile::{TILE_HEIGHT, TILE_WIDTH};
-use crate::resources::PlayerHand;
+use crate::events::*;
+use crate::plugins::game::{AppState, PlayPhase};
+use crate::plugins::gamepad::ButtonFocus;
+use crate::plugins::tile::{BOARD_Y, HAND_Y, TILE_HEIGHT, TILE_WIDTH};
+use crate::resources::{GameState, PlayerHand, SortMode};
+
+/// Keys toggling hand tiles by position: 1–9, 0, then the top letter row
+const HAND_KEYS: [KeyCode; 20] = [
+    KeyCode::Digit1,
+    KeyCode::Digit2,
+    KeyCode::Digit3,
+    KeyCode::Digit4,
+    KeyCode::Digit5,
+    KeyCode::Digit6,
+    KeyCode::Digit7,
+    KeyCode::Digit8,
+    KeyCode::Digit9,
+    KeyCode::Digit0,
+    KeyCode::KeyQ,
+    KeyCode::KeyW,
+    KeyCode::KeyE,
+    KeyCode::KeyR,
+    KeyCode::KeyT,
+    KeyCode::KeyY,
+    KeyCode::KeyU,
+    KeyCode::KeyI,
+    KeyCode::KeyO,
+    KeyCode::KeyP,
+];
+
+const FOCUS_FRAME_COLOR: Color = Color::srgb(0.95, 0.8, 0.2);
+/// How far the focus frame sticks out around the focused tile
+const FOCUS_FRAME_MARGIN: f32 = 4.0;
+
+/// Distance the cursor moves, while held, before a press becomes a drag
+const DRAG_THRESHOLD: f32 = 8.0;
+/// Hovered tiles grow by this factor
+const HOVER_SCALE: f32 = 1.08;
+/// Dragged tiles draw over everything else on the table
+const DRAGGED_TILE_Z: f32 = 5.0;
+/// Selection sweeps start within this distance above or below the hand row
+const HAND_BAND_HALF_HEIGHT: f32 = TILE_HEIGHT / 2.0 + 15.0;
+/// Tiles dropped above this line are played
+const PLAY_DROP_MIN_Y: f32 = BOARD_Y - 2.0 * TILE_HEIGHT;
+/// Seconds a second quit press has to confirm leaving the run
+const QUIT_CONFIRM_SECONDS: f32 = 2.0;
 
 pub struct InputPlugin;
 
 impl Plugin for InputPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(
-            Update,
-            handle_tile_click.run_if(in_state(AppState::Playing)),
-        );
+        app.init_resource::<HandCursor>()
+            .init_resource::<TileDrag>()
+            .init_resource::<QuitPrompt>()
+            // UI nodes opt in to picking so the layout doesn't cover the tiles
+            .insert_resource(UiPickingSettings { require_markers: true })
+            .add_observer(on_pointer_click)
+            .add_observer(on_tile_clicked)
+            .add_observer(on_pointer_over)
+            .add_observer(on_pointer_out)
+            .add_observer(on_drag_start)
+            .add_observer(on_drag)
+            .add_observer(on_drag_end)
+            .add_observer(on_quit_to_menu)
+            .add_systems(OnEnter(AppState::Playing), spawn_focus_frame)
+            .add_systems(OnExit(AppState::Playing), despawn_focus_frame)
+            .add_systems(OnExit(PlayPhase::Selecting), cancel_drag)
+            .add_systems(
+                Update,
+                (
+                    handle_hand_keys.run_if(in_state(PlayPhase::Selecting)),
+                    handle_action_keys,
+                    update_focus_frame,
+                )
+                    .chain()
+                    .run_if(in_state(AppState::Playing)),
+            );
     }
 }
 
-fn handle_tile_click(
-    mouse: Res<ButtonInput<MouseButton>>,
-    windows: Query<&Window>,
-    camera_q: Query<(&Camera, &GlobalTransform)>,
-    hand: Res<PlayerHand>,
+/// Hand position focused with the arrow keys or D-pad; `None` until first moved
+#[derive(Resource, Default)]
+pub struct HandCursor(pub Option<usize>);
+
+impl HandCursor {
+    /// Move left (-1) or right (+1) through a hand of `len` tiles, wrapping around.
+    /// The first step from nowhere lands on the leftmost tile.
+    pub fn step(&mut self, delta: i32, len: usize) {
+        if len == 0 {
+            return;
+        }
+        self.0 = Some(match self.0 {
+            Some(i) => (i as i32 + delta).rem_euclid(len as i32) as usize,
+            None => 0,
+        });
+    }
+}
+
+/// A left-button drag on the table, from drag start to drag end
+/// When a first quit press asked for confirmation
+#[derive(Resource, Default)]
+pub struct QuitPrompt {
+    asked_at: Option<f32>,
+}
+
+#[derive(Resource, Default)]
+pub struct TileDrag {
+    /// Where the drag started, in world space
+    start: Option<Vec2>,
+    /// Hand tile being dragged, if any
+    tile: Option<Entity>,
+    /// Moved past `DRAG_THRESHOLD`, so releasing isn't a click
+    dragging: bool,
+}
+
+/// Outline drawn behind the focused hand tile
+#[derive(Component)]
+pub struct FocusFrame;
+
+pub fn toggle_selected(commands: &mut Commands, entity: Entity, is_selected: bool) {
+    if is_selected {
+        commands.entity(entity).remove::<TileSelected>();
+    } else {
+        commands.entity(entity).insert(TileSelected);
+    }
+}
+
+fn in_selecting(phase: &Option<Res<State<PlayPhase>>>) -> bool {
+    phase.as_ref().is_some_and(|p| *p.get() == PlayPhase::Selecting)
+}
+
+/// Pointer position in world space
+fn pointer_world(location: &Location, camera_q: &Query<(&Camera, &GlobalTransform)>) -> Option<Vec2> {
+    let (camera, transform) = camera_q.single().ok()?;
+    camera.viewport_to_world_2d(transform, location.position).ok()
+}
+
+/// Left click fires `TileClickedEvent`, right click inspects; clicks ending a drag are ignored
+fn on_pointer_click(
+    mut click: On<Pointer<Click>>,
+    drag: Res<TileDrag>,
+    tile_q: Query<(), With<Tile>>,
     mut commands: Commands,
-    tile_q: Query<(&Transform, Has<TileSelected>), With<Tile>>,
 ) {
-    if !mouse.just_pressed(MouseButton::Left) {
+    let entity = click.entity;
+    if drag.dragging || !tile_q.contains(entity) {
         return;
     }
+    click.propagate(false);
+    match click.button {
+        PointerButton::Primary => commands.trigger(TileClickedEvent { tile_entity: entity }),
+        PointerButton::Secondary => commands.trigger(InspectTileEvent { tile_entity: entity }),
+        PointerButton::Middle => {}
+    }
+}
 
-    let Ok(window) = windows.single() else {
+/// Observer: a clicked hand tile toggles its selection and takes the cursor
+fn on_tile_clicked(
+    trigger: On<TileClickedEvent>,
+    phase: Option<Res<State<PlayPhase>>>,
+    hand: Res<PlayerHand>,
+    mut cursor: ResMut<HandCursor>,
+    mut commands: Commands,
+    tile_q: Query<Has<TileSelected>, With<Tile>>,
+) {
+    let entity = trigger.event().tile_entity;
+    if !in_selecting(&phase) {
         return;
-    };
-    let Some(cursor_pos) = window.cursor_position() else {
+    }
+    let Some(position) = hand.tiles.iter().position(|&e| e == entity) else {
         return;
     };
-    let Ok((camera, camera_transform)) = camera_q.single() else {
+    toggle_selected(&mut commands, entity, tile_q.get(entity).unwrap_or(false));
+    cursor.0 = Some(position);
+}
+
+/// Hovered tiles grow slightly and bob
+fn on_pointer_over(
+    over: On<Pointer<Over>>,
+    mut commands: Commands,
+    mut tile_q: Query<&mut Transform, With<Tile>>,
+) {
+    if let Ok(mut transform) = tile_q.get_mut(over.entity) {
+        transform.scale = Vec3::splat(HOVER_SCALE);
+        commands.entity(over.entity).try_insert(TileHovered);
+    }
+}
+
+fn on_pointer_out(
+    out: On<Pointer<Out>>,
+    mut commands: Commands,
+    mut tile_q: Query<&mut Transform, With<Tile>>,
+) {
+    if let Ok(mut transform) = tile_q.get_mut(out.entity) {
+        transform.scale = Vec3::ONE;
+        commands.entity(out.entity).try_remove::<TileHovered>();
+    }
+}
+
+/// Remember where a left drag started and which hand tile it picked up, if any
+fn on_drag_start(
+    mut start: On<Pointer<DragStart>>,
+    phase: Option<Res<State<PlayPhase>>>,
+    hand: Res<PlayerHand>,
+    mut drag: ResMut<TileDrag>,
+    camera_q: Query<(&Camera, &GlobalTransform)>,
+) {
+    start.propagate(false);
+    if start.button != PointerButton::Primary || !in_selecting(&phase) {
         return;
+    }
+    *drag = TileDrag {
+        start: pointer_world(&start.pointer_location, &camera_q),
+        tile: hand.tiles.contains(&start.entity).then_some(start.entity),
+        dragging: false,
     };
-    let Ok(world_pos) = camera.viewport_to_world_2d(camera_transform, cursor_pos) else {
+}
+
+/// Past `DRAG_THRESHOLD` the dragged tile follows the pointer,
+/// or a drag starting beside the hand sweeps a selection
+fn on_drag(
+    mut event: On<Pointer<Drag>>,
+    hand: Res<PlayerHand>,
+    mut drag: ResMut<TileDrag>,
+    mut commands: Commands,
+    camera_q: Query<(&Camera, &GlobalTransform)>,
+    mut tile_q: Query<(&mut Transform, Has<TileSelected>), With<Tile>>,
+) {
+    event.propagate(false);
+    let Some(start) = drag.start else { return };
+    if event.button != PointerButton::Primary
+        || (!drag.dragging && event.distance.length() < DRAG_THRESHOLD)
+    {
+        return;
+    }
+    let Some(cursor) = pointer_world(&event.pointer_location, &camera_q) else {
         return;
     };
+    drag.dragging = true;
 
-    // Check each hand tile for AABB collision
-    for &entity in hand.tiles.iter() {
-        if let Ok((transform, is_selected)) = tile_q.get(entity) {
-            let tile_pos = transform.translation.truncate();
-            let half_w = TILE_WIDTH / 2.0;
-            let half_h = TILE_HEIGHT / 2.0;
-
-            if world_pos.x >= tile_pos.x - half_w
-                && world_pos.x <= tile_pos.x + half_w
-                && world_pos.y >= tile_pos.y - half_h
-                && world_pos.y <= tile_pos.y + half_h
-            {
-                if is_selected {
-                    commands.entity(entity).remove::<TileSelected>();
-                } else {
+    if let Some(entity) = drag.tile {
+        commands.entity(entity).insert(Dragging);
+        if let Ok((mut transform, _)) = tile_q.get_mut(entity) {
+            transform.translation = cursor.extend(DRAGGED_TILE_Z);
+        }
+    } else if (start.y - HAND_Y).abs() <= HAND_BAND_HALF_HEIGHT {
+        let (left, right) = (start.x.min(cursor.x), start.x.max(cursor.x));
+        for &entity in &hand.tiles {
+            if let Ok((transform, false)) = tile_q.get(entity) {
+                let x = transform.translation.x;
+                if x + TILE_WIDTH / 2.0 >= left && x - TILE_WIDTH / 2.0 <= right {
                     commands.entity(entity).insert(TileSelected);
                 }
-                break; // only toggle one tile per click
             }
         }
     }
 }
+
+/// A tile dropped on the play area is played along with the rest of the
+/// selection, and one dropped on the hand moves to that slot, switching
+/// the hand to manual sorting
+fn on_drag_end(
+    mut end: On<Pointer<DragEnd>>,
+    mut hand: ResMut<PlayerHand>,
+    mut drag: ResMut<TileDrag>,
+    mut sort_mode: ResMut<SortMode>,
+    mut commands: Commands,
+    camera_q: Query<(&Camera, &GlobalTransform)>,
+    tile_q: Query<&Transform, With<Tile>>,
+) {
+    end.propagate(false);
+    if end.button != PointerButton::Primary {
+        return;
+    }
+    let TileDrag { tile, dragging, .. } = std::mem::take(&mut *drag);
+    let Some(entity) = tile.filter(|_| dragging) else { return };
+
+    commands.entity(entity).remove::<Dragging>();
+    let Some(cursor) = pointer_world(&end.pointer_location, &camera_q) else {
+        return;
+    };
+    if cursor.y >= PLAY_DROP_MIN_Y {
+        commands.entity(entity).insert(TileSelected);
+        commands.trigger(PlayTilesEvent);
+        return;
+    }
+
+    let Some(from) = hand.tiles.iter().position(|&e| e == entity) else { return };
+    hand.tiles.remove(from);
+    let to = drop_slot(tile_q.iter_many(&hand.tiles).map(|t| t.translation.x), cursor.x);
+    hand.tiles.insert(to, entity);
+    *sort_mode = SortMode::Manual;
+}
+
+/// Hand slot for a tile dropped at `x`: after every other hand tile left of it
+fn drop_slot(tile_xs: impl IntoIterator<Item = f32>, x: f32) -> usize {
+    tile_xs.into_iter().filter(|&tile_x| tile_x < x).count()
+}
+
+/// Put down whatever was being dragged when the phase moves on mid-drag
+fn cancel_drag(
+    mut drag: ResMut<TileDrag>,
+    mut commands: Commands,
+    dragged_q: Query<Entity, With<Dragging>>,
+) {
+    for entity in &dragged_q {
+        commands.entity(entity).remove::<Dragging>();
+    }
+    *drag = TileDrag::default();
+}
+
+/// Number/letter keys toggle the tile at that position; arrows move the
+/// cursor, Up/Space toggles the focused tile and Down deselects it
+fn handle_hand_keys(
+    keys: Res<ButtonInput<KeyCode>>,
+    hand: Res<PlayerHand>,
+    mut cursor: ResMut<HandCursor>,
+    mut commands: Commands,
+    tile_q: Query<Has<TileSelected>, With<Tile>>,
+) {
+    let len = hand.tiles.len();
+    if len == 0 {
+        return;
+    }
+
+    if let Some(position) = HAND_KEYS.iter().position(|&key| keys.just_pressed(key)) {
+        if let Some(&entity) = hand.tiles.get(position) {
+            toggle_selected(&mut commands, entity, tile_q.get(entity).unwrap_or(false));
+            cursor.0 = Some(position);
+        }
+        return;
+    }
+
+    if keys.just_pressed(KeyCode::ArrowLeft) {
+        cursor.step(-1, len);
+    }
+    if keys.just_pressed(KeyCode::ArrowRight) {
+        cursor.step(1, len);
+    }
+
+    let Some(focused) = cursor.0.and_then(|i| hand.tiles.get(i)).copied() else {
+        return;
+    };
+    let is_selected = tile_q.get(focused).unwrap_or(false);
+    if keys.just_pressed(KeyCode::ArrowUp) && !is_selected {
+        commands.entity(focused).insert(TileSelected);
+    } else if keys.just_pressed(KeyCode::ArrowDown) && is_selected {
+        commands.entity(focused).remove::<TileSelected>();
+    } else if keys.just_pressed(KeyCode::Space) {
+        toggle_selected(&mut commands, focused, is_selected);
+    }
+}
+
+/// Enter plays, Backspace/D discards, Z takes back the last action, Esc twice returns to the menu, S cycles the sort mode
+fn handle_action_keys(
+    keys: Res<ButtonInput<KeyCode>>,
+    phase: Res<State<PlayPhase>>,
+    mut sort_mode: ResMut<SortMode>,
+    mut commands: Commands,
+) {
+    if *phase.get() == PlayPhase::Selecting {
+        if keys.just_pressed(KeyCode::Enter) {
+            commands.trigger(PlayTilesEvent);
+        }
+        if keys.any_just_pressed([KeyCode::Backspace, KeyCode::KeyD]) {
+            commands.trigger(DiscardTilesEvent);
+        }
+        if keys.just_pressed(KeyCode::KeyZ) {
+            commands.trigger(UndoEvent);
+        }
+    }
+    if keys.just_pressed(KeyCode::KeyS) {
+        *sort_mode = sort_mode.next();
+    }
+    if keys.just_pressed(KeyCode::Escape) {
+        commands.trigger(QuitToMenuEvent);
+    }
+}
+
+/// Observer: the first quit press asks for confirmation; a second one within
+/// `QUIT_CONFIRM_SECONDS` abandons the run and returns to the menu
+fn on_quit_to_menu(
+    _trigger: On<QuitToMenuEvent>,
+    time: Res<Time>,
+    mut prompt: ResMut<QuitPrompt>,
+    mut game_state: ResMut<GameState>,
+    mut next_state: ResMut<NextState<AppState>>,
+    mut commands: Commands,
+) {
+    let now = time.elapsed_secs();
+    match prompt.asked_at.take() {
+        Some(asked_at) if now - asked_at <= QUIT_CONFIRM_SECONDS => {
+            game_state.reset_for_new_game();
+            next_state.set(AppState::Menu);
+        }
+        _ => {
+            prompt.asked_at = Some(now);
+            commands.trigger(PlayRejectedEvent {
+                reason: "再按一次返回菜单",
+            });
+        }
+    }
+}
+
+fn spawn_focus_frame(mut commands: Commands, mut cursor: ResMut<HandCursor>) {
+    cursor.0 = None;
+    commands.spawn((
+        FocusFrame,
+        Sprite::from_color(
+            FOCUS_FRAME_COLOR,
+            Vec2::new(
+                TILE_WIDTH + 2.0 * FOCUS_FRAME_MARGIN,
+                TILE_HEIGHT + 2.0 * FOCUS_FRAME_MARGIN,
+            ),
+        ),
+        Transform::from_translation(Vec3::new(0.0, 0.0, 1.5)),
+        Visibility::Hidden,
+    ));
+}
+
+fn despawn_focus_frame(mut commands: Commands, frame_q: Query<Entity, With<FocusFrame>>) {
+    for entity in &frame_q {
+        commands.entity(entity).despawn();
+    }
+}
+
+/// Keep the focus frame behind the focused hand tile (unless a button has focus), clamping the cursor as the hand shrinks
+fn update_focus_frame(
+    hand: Res<PlayerHand>,
+    phase: Res<State<PlayPhase>>,
+    mut cursor: ResMut<HandCursor>,
+    button_focus: Res<ButtonFocus>,
+    tile_q: Query<&Transform, (With<Tile>, Without<FocusFrame>)>,
+    mut frame_q: Query<(&mut Transform, &mut Visibility), With<FocusFrame>>,
+) {
+    let Ok((mut frame, mut visibility)) = frame_q.single_mut() else {
+        return;
+    };
+    if let Some(i) = cursor.0 {
+        if i >= hand.tiles.len() {
+            cursor.0 = hand.tiles.len().checked_sub(1);
+        }
+    }
+    let focused = cursor
+        .0
+        .and_then(|i| hand.tiles.get(i))
+        .and_then(|&e| tile_q.get(e).ok())
+        .filter(|_| *phase.get() == PlayPhase::Selecting && button_focus.button.is_none());
+    match focused {
+        Some(tile) => {
+            frame.translation = tile.translation.truncate().extend(1.5);
+            *visibility = Visibility::Inherited;
+        }
+        None => *visibility = Visibility::Hidden,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resources::Stake;
+    use std::time::Duration;
+
+    fn quit_world() -> World {
+        let mut world = World::new();
+        world.init_resource::<Time>();
+        world.init_resource::<QuitPrompt>();
+        world.init_resource::<NextState<AppState>>();
+        world.insert_resource(GameState {
+            level: 3,
+            ..GameState::new(Stake::Red)
+        });
+        world.add_observer(on_quit_to_menu);
+        world
+    }
+
+    fn quitting(world: &World) -> bool {
+        matches!(
+            world.resource::<NextState<AppState>>(),
+            NextState::Pending(AppState::Menu)
+        )
+    }
+
+    #[test]
+    fn test_quit_needs_confirming() {
+        let mut world = quit_world();
+        world.trigger(QuitToMenuEvent);
+        world.flush();
+        assert!(!quitting(&world));
+        assert_eq!(world.resource::<GameState>().level, 3);
+
+        world.trigger(QuitToMenuEvent);
+        world.flush();
+        assert!(quitting(&world));
+        let game_state = world.resource::<GameState>();
+        assert_eq!(game_state.level, 1);
+        assert_eq!(game_state.stake, Stake::Red);
+    }
+
+    #[test]
+    fn test_quit_prompt_expires() {
+        let mut world = quit_world();
+        world.trigger(QuitToMenuEvent);
+        let late = Duration::from_secs_f32(QUIT_CONFIRM_SECONDS + 1.0);
+        world.resource_mut::<Time>().advance_by(late);
+        world.trigger(QuitToMenuEvent);
+        world.flush();
+        assert!(!quitting(&world));
+
+        // ...but that late press asks again
+        world.trigger(QuitToMenuEvent);
+        world.flush();
+        assert!(quitting(&world));
+    }
+
+    #[test]
+    fn test_drop_slot() {
+        // The rest of the hand, once the dragged tile is lifted out
+        let xs = [-78.0, -26.0, 26.0, 78.0];
+        assert_eq!(drop_slot(xs, -200.0), 0);
+        assert_eq!(drop_slot(xs, 0.0), 2);
+        assert_eq!(drop_slot(xs, 200.0), 4);
+
+        // Dragging the first tile to between the third and fourth
+        let mut hand = vec!['a', 'b', 'c', 'd'];
+        let tile = hand.remove(0);
+        hand.insert(drop_slot([-26.0, 26.0, 78.0], 50.0), tile);
+        assert_eq!(hand, ['b', 'c', 'a', 'd']);
+    }
+}
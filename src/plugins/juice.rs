@@ -0,0 +1,224 @@
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::components::tile::Tile;
+use crate::events::*;
+use crate::plugins::game::{AppState, MainCamera};
+use crate::plugins::scoring::{HandPattern, ScoreEffect};
+use crate::plugins::tile::{BOARD_Y, TILE_HEIGHT};
+
+/// Seconds a score popup floats before it's gone, and how far it rises
+const POPUP_SECONDS: f32 = 1.2;
+const POPUP_RISE: f32 = 60.0;
+const POPUP_COLOR: Color = Color::srgb(1.0, 0.85, 0.2);
+/// Step popups: chips in blue, +mult in red, ×mult in purple
+const STEP_POPUP_SIZE: f32 = 18.0;
+const CHIPS_COLOR: Color = Color::srgb(0.35, 0.65, 1.0);
+const ADD_MULT_COLOR: Color = Color::srgb(1.0, 0.35, 0.3);
+const X_MULT_COLOR: Color = Color::srgb(0.8, 0.45, 1.0);
+
+/// Trauma from a 清一色; limit hands like 国士无双 shake hardest
+const BIG_PATTERN_TRAUMA: f32 = 0.6;
+/// Camera offset at full trauma, and how much trauma wears off per second
+const SHAKE_MAX_OFFSET: f32 = 12.0;
+const SHAKE_DECAY: f32 = 1.5;
+
+pub struct JuicePlugin;
+
+impl Plugin for JuicePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ScreenShake>()
+            .add_observer(on_score_popup)
+            .add_observer(on_score_step)
+            .add_systems(Update, (float_popups, shake_camera))
+            .add_systems(OnExit(AppState::Playing), despawn_popups);
+    }
+}
+
+/// Text that rises and fades over the board, then despawns
+#[derive(Component)]
+struct ScorePopup {
+    elapsed: f32,
+    start_y: f32,
+    color: Color,
+}
+
+/// Trauma from 0 to 1; the camera shakes by its square while it wears off
+#[derive(Resource, Default)]
+pub struct ScreenShake {
+    pub trauma: f32,
+}
+
+impl ScreenShake {
+    pub fn add(&mut self, trauma: f32) {
+        self.trauma = (self.trauma + trauma).min(1.0);
+    }
+}
+
+/// Observer: pop the scored total up over the board, and shake the
+/// screen for big patterns
+fn on_score_popup(
+    trigger: On<ScoreCalculatedEvent>,
+    mut commands: Commands,
+    mut shake: ResMut<ScreenShake>,
+) {
+    let event = trigger.event();
+    let start_y = BOARD_Y + TILE_HEIGHT;
+    commands.spawn((
+        ScorePopup {
+            elapsed: 0.0,
+            start_y,
+            color: POPUP_COLOR,
+        },
+        Text2d::new(format!("+{}", event.total)),
+        TextFont {
+            font_size: 32.0,
+            ..default()
+        },
+        TextColor(POPUP_COLOR),
+        Transform::from_translation(Vec3::new(0.0, start_y, 6.0)),
+    ));
+
+    // Keyed on the pattern itself, not its levelled or ruleset-scaled mult
+    let pattern = event.hand.pattern;
+    if pattern.is_limit() {
+        shake.add(1.0);
+    } else if pattern == HandPattern::FullFlush {
+        shake.add(BIG_PATTERN_TRAUMA);
+    }
+}
+
+/// What a step popup says, and in which color
+fn step_popup(effect: &ScoreEffect) -> (String, Color) {
+    match effect {
+        ScoreEffect::Chips(chips) => (format!("+{chips}"), CHIPS_COLOR),
+        ScoreEffect::AddMult(mult) => (format!("+{mult} Mult"), ADD_MULT_COLOR),
+        ScoreEffect::XMult(mult) => (format!("x{mult} Mult"), X_MULT_COLOR),
+    }
+}
+
+/// Observer: pop each scoring step up over the tile it came from,
+/// or under the board for hand-wide effects
+fn on_score_step(
+    trigger: On<ScoreStepEvent>,
+    mut commands: Commands,
+    tile_q: Query<&Transform, With<Tile>>,
+) {
+    let event = trigger.event();
+    let (label, color) = step_popup(&event.effect);
+    let at = match event.tile.and_then(|tile| tile_q.get(tile).ok()) {
+        Some(transform) => transform.translation.truncate() + Vec2::Y * TILE_HEIGHT * 0.6,
+        None => Vec2::new(0.0, BOARD_Y - TILE_HEIGHT),
+    };
+    commands.spawn((
+        ScorePopup {
+            elapsed: 0.0,
+            start_y: at.y,
+            color,
+        },
+        Text2d::new(label),
+        TextFont {
+            font_size: STEP_POPUP_SIZE,
+            ..default()
+        },
+        TextColor(color),
+        Transform::from_translation(at.extend(6.0)),
+    ));
+}
+
+fn float_popups(
+    time: Res<Time>,
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut ScorePopup, &mut Transform, &mut TextColor)>,
+) {
+    for (entity, mut popup, mut transform, mut color) in &mut query {
+        popup.elapsed += time.delta_secs();
+        let t = popup.elapsed / POPUP_SECONDS;
+        if t >= 1.0 {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        transform.translation.y = popup.start_y + POPUP_RISE * (1.0 - (1.0 - t).powi(2));
+        color.0 = popup.color.with_alpha(1.0 - t * t);
+    }
+}
+
+fn despawn_popups(mut commands: Commands, query: Query<Entity, With<ScorePopup>>) {
+    for entity in &query {
+        commands.entity(entity).despawn();
+    }
+}
+
+/// Jitter the camera around the origin while there's trauma left
+fn shake_camera(
+    time: Res<Time>,
+    mut shake: ResMut<ScreenShake>,
+    mut camera_q: Query<&mut Transform, With<MainCamera>>,
+) {
+    let Ok(mut transform) = camera_q.single_mut() else {
+        return;
+    };
+    if shake.trauma <= 0.0 {
+        if transform.translation.x != 0.0 || transform.translation.y != 0.0 {
+            transform.translation = transform.translation.with_x(0.0).with_y(0.0);
+        }
+        return;
+    }
+    let mut rng = rand::thread_rng();
+    let amount = SHAKE_MAX_OFFSET * shake.trauma * shake.trauma;
+    transform.translation.x = rng.gen_range(-1.0..=1.0) * amount;
+    transform.translation.y = rng.gen_range(-1.0..=1.0) * amount;
+    shake.trauma = (shake.trauma - SHAKE_DECAY * time.delta_secs()).max(0.0);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_step_popups_by_effect() {
+        let mut world = World::new();
+        world.add_observer(on_score_step);
+        let tile = world
+            .spawn((
+                Tile::generate_full_set().remove(0),
+                Transform::from_xyz(100.0, 40.0, 1.0),
+            ))
+            .id();
+
+        let effects = [
+            ScoreEffect::Chips(5u32.into()),
+            ScoreEffect::AddMult(4u32.into()),
+            ScoreEffect::XMult(2u32.into()),
+        ];
+        for effect in effects {
+            world.trigger(ScoreStepEvent {
+                effect,
+                tile: Some(tile),
+            });
+        }
+        world.trigger(ScoreStepEvent {
+            effect: ScoreEffect::Chips(20u32.into()),
+            tile: None,
+        });
+        world.flush();
+
+        let mut popups: Vec<(String, Color, Vec3)> = world
+            .query::<(&Text2d, &TextColor, &Transform)>()
+            .iter(&world)
+            .map(|(text, color, transform)| (text.0.clone(), color.0, transform.translation))
+            .collect();
+        popups.sort_by(|a, b| a.0.cmp(&b.0));
+        let labels: Vec<&str> = popups.iter().map(|p| p.0.as_str()).collect();
+        assert_eq!(labels, vec!["+20", "+4 Mult", "+5", "x2 Mult"]);
+        let colors: Vec<Color> = popups.iter().map(|p| p.1).collect();
+        assert_eq!(
+            colors,
+            vec![CHIPS_COLOR, ADD_MULT_COLOR, CHIPS_COLOR, X_MULT_COLOR]
+        );
+        // Tile steps rise over their tile, hand-wide ones sit under the board
+        assert_eq!(popups[2].2.x, 100.0);
+        assert_eq!(popups[0].2.x, 0.0);
+        assert!(popups[0].2.y < BOARD_Y);
+    }
+}
@@ -1,8 +1,13 @@
 pub mod board;
 pub mod game;
+pub mod gamepad;
 pub mod input;
+pub mod juice;
+pub mod ruleset;
 pub mod scoring;
+pub mod shop;
 pub mod tile;
+pub mod tile_faces;
 pub mod ui;
 
 use bevy::app::{PluginGroup, PluginGroupBuilder};
@@ -14,9 +19,14 @@ impl PluginGroup for RedCenterPluginGroup {
         PluginGroupBuilder::start::<Self>()
             .add(game::GamePlugin)
             .add(tile::TilePlugin)
+            .add(tile_faces::TileFacesPlugin)
             .add(board::BoardPlugin)
             .add(input::InputPlugin)
+            .add(gamepad::GamepadPlugin)
+            .add(ruleset::RulesetPlugin)
             .add(scoring::ScoringPlugin)
+            .add(shop::ShopPlugin)
             .add(ui::UiPlugin)
+            .add(juice::JuicePlugin)
     }
 }
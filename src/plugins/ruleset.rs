@@ -0,0 +1,522 @@
+use bevy::prelude::*;
+
+use crate::components::tile::*;
+use crate::plugins::scoring::*;
+use crate::resources::PatternLevels;
+
+pub struct RulesetPlugin;
+
+impl Plugin for RulesetPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RulesetKind>();
+    }
+}
+
+/// Regional rules a run is played under: which tiles are dealt, what counts
+/// as a win and how a winning hand's pattern is scored.
+pub trait Ruleset: Send + Sync {
+    /// Every tile a run is dealt from, flowers and seasons included
+    fn generate_deck(&self) -> Vec<Tile> {
+        let mut deck = Tile::generate_full_set();
+        deck.extend(Tile::generate_bonus_set());
+        deck
+    }
+
+    /// Whether the tiles (kongs counted as triplets) are a legal win
+    fn is_winning(&self, tiles: &[TileId]) -> bool;
+
+    /// The pattern and starting chips/mult of a hand
+    fn evaluate(&self, tiles: &[TileId], levels: &PatternLevels) -> HandResult;
+}
+
+/// The rulesets selectable on the menu
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RulesetKind {
+    #[default]
+    Riichi,
+    ChineseOfficial,
+    HongKong,
+    Sichuan,
+}
+
+impl RulesetKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            RulesetKind::Riichi => "立直麻将",
+            RulesetKind::ChineseOfficial => "国标麻将",
+            RulesetKind::HongKong => "港式麻将",
+            RulesetKind::Sichuan => "四川血战",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            RulesetKind::Riichi => RulesetKind::ChineseOfficial,
+            RulesetKind::ChineseOfficial => RulesetKind::HongKong,
+            RulesetKind::HongKong => RulesetKind::Sichuan,
+            RulesetKind::Sichuan => RulesetKind::Riichi,
+        }
+    }
+
+    pub fn rules(&self) -> &'static dyn Ruleset {
+        match self {
+            RulesetKind::Riichi => &RiichiRules,
+            RulesetKind::ChineseOfficial => &ChineseOfficialRules,
+            RulesetKind::HongKong => &HongKongRules,
+            RulesetKind::Sichuan => &SichuanRules,
+        }
+    }
+
+    /// The rules with a blind's 定缺 suit applied, if one was declared
+    pub fn rules_for(&self, void_suit: Option<TileSuit>) -> VoidRules {
+        VoidRules {
+            rules: self.rules(),
+            void_suit,
+        }
+    }
+}
+
+/// Rules under 定缺: a hand holding the void suit (花猪) never wins
+pub struct VoidRules {
+    rules: &'static dyn Ruleset,
+    void_suit: Option<TileSuit>,
+}
+
+impl Ruleset for VoidRules {
+    fn generate_deck(&self) -> Vec<Tile> {
+        self.rules.generate_deck()
+    }
+
+    fn is_winning(&self, tiles: &[TileId]) -> bool {
+        let void = |t: &TileId| Some(t.suit) == self.void_suit;
+        !tiles.iter().any(void) && self.rules.is_winning(tiles)
+    }
+
+    fn evaluate(&self, tiles: &[TileId], levels: &PatternLevels) -> HandResult {
+        self.void_suit
+            .and_then(|suit| void_broken(tiles, suit))
+            .unwrap_or_else(|| self.rules.evaluate(tiles, levels))
+    }
+}
+
+// ============ Riichi (default) ============
+
+/// The game's original rules: any standard hand, 七对子 or 国士无双 wins,
+/// scored by its best single pattern
+pub struct RiichiRules;
+
+impl Ruleset for RiichiRules {
+    fn is_winning(&self, tiles: &[TileId]) -> bool {
+        detect_pattern(tiles).is_winning()
+    }
+
+    fn evaluate(&self, tiles: &[TileId], levels: &PatternLevels) -> HandResult {
+        evaluate_hand(tiles, levels)
+    }
+}
+
+// ============ Fan-based rulesets ============
+
+/// A scoring element and what it's worth, e.g. ("清一色", 24)
+type Fan = (&'static str, u32);
+
+/// Chips every fan-scored hand starts from
+const FAN_BASE_CHIPS: u32 = 10;
+
+fn is_triplet(meld: &Meld) -> bool {
+    matches!(meld.kind, MeldKind::Triplet | MeldKind::Kong)
+}
+
+fn number_suits(tiles: &[TileId]) -> usize {
+    [TileSuit::Man, TileSuit::Pin, TileSuit::Sou]
+        .iter()
+        .filter(|&&suit| tiles.iter().any(|t| t.suit == suit))
+        .count()
+}
+
+/// Every meld is a sequence and the pair isn't an honor
+fn is_all_sequences(melds: &[Meld]) -> bool {
+    melds.iter().all(|m| match m.kind {
+        MeldKind::Sequence => true,
+        MeldKind::Pair => !m.first.is_honor(),
+        MeldKind::Triplet | MeldKind::Kong => false,
+    }) && melds.iter().filter(|m| m.kind == MeldKind::Pair).count() == 1
+}
+
+/// A winning hand scored from its fans, named after them.
+/// The shop's pattern levels apply through the closest default pattern.
+fn fan_result(tiles: &[TileId], levels: &PatternLevels, fans: &[Fan], multiplier: u32) -> HandResult {
+    // 龙七对 counts a quad as two pairs, which the default 七对子 doesn't
+    let pattern = match detect_pattern(tiles) {
+        HandPattern::NoWin => HandPattern::SevenPairs,
+        pattern => pattern,
+    };
+    let level = levels.level(pattern);
+    let (base_bonus, mult_bonus) = pattern.level_bonus();
+    let total: u32 = fans.iter().map(|(_, value)| value).sum();
+    let names: Vec<&str> = fans.iter().map(|(name, _)| *name).collect();
+    HandResult {
+        pattern_name: format!("{} {}番", names.join("+"), total),
+        pattern,
+        winning: true,
+        level,
+        base: (FAN_BASE_CHIPS + base_bonus * (level - 1)).into(),
+        multiplier: (multiplier + mult_bonus * (level - 1)).into(),
+    }
+}
+
+/// A hand that isn't a legal win, optionally because it fell short of the fan floor
+fn no_win(tiles: &[TileId], fans: Option<(u32, u32)>) -> HandResult {
+    let mut result = evaluate_hand(tiles, &PatternLevels::default());
+    if result.winning {
+        result = HandResult {
+            pattern: HandPattern::NoWin,
+            winning: false,
+            base: (tiles.len() as u32).into(),
+            multiplier: 1u32.into(),
+            ..result
+        };
+    }
+    if let Some((total, min)) = fans {
+        result.pattern_name = format!("{total}番 未满{min}番起和");
+    }
+    result
+}
+
+/// 定缺: a hand holding its void suit (花猪) doesn't win
+pub fn void_broken(tiles: &[TileId], suit: TileSuit) -> Option<HandResult> {
+    if !tiles.iter().any(|t| t.suit == suit) {
+        return None;
+    }
+    let mut result = no_win(tiles, None);
+    result.pattern_name = format!("未缺{}", suit.label());
+    Some(result)
+}
+
+// ============ Chinese Official (国标) ============
+
+/// 国标: hands need at least 8 fan to win
+const MCR_MIN_FAN: u32 = 8;
+
+/// Chinese Official rules with a subset of the 81 fan.
+/// Fan stack, and a hand must reach `MCR_MIN_FAN` to count as a win.
+pub struct ChineseOfficialRules;
+
+impl ChineseOfficialRules {
+    fn fans(tiles: &[TileId]) -> Vec<Fan> {
+        let counts = build_count_array(tiles);
+        if check_thirteen_orphans(&counts) {
+            return vec![("十三幺", 88)];
+        }
+        let Some(melds) = decompose_hand(tiles) else {
+            return Vec::new();
+        };
+        let standard = check_standard(&counts);
+
+        let mut fans = Vec::new();
+        if !standard {
+            fans.push(("七对", 24));
+        }
+        if check_chinitsu(tiles) {
+            fans.push(("清一色", 24));
+        } else if check_honitsu(tiles) {
+            fans.push(("混一色", 6));
+        }
+        if standard && check_straight(&counts) {
+            fans.push(("清龙", 16));
+        }
+        if check_toitoi(&counts) {
+            fans.push(("碰碰和", 6));
+        }
+        if check_tanyao(tiles) {
+            fans.push(("断幺", 2));
+        }
+        if is_all_sequences(&melds) {
+            fans.push(("平和", 2));
+        }
+        for meld in melds.iter().filter(|m| is_triplet(m)) {
+            if meld.first.suit == TileSuit::Dragon {
+                fans.push(("箭刻", 2));
+            } else if meld.first.is_honor() || meld.first.is_terminal() {
+                fans.push(("幺九刻", 1));
+            }
+        }
+        if number_suits(tiles) == 2 {
+            fans.push(("缺一门", 1));
+        }
+        if !tiles.iter().any(|t| t.is_honor()) {
+            fans.push(("无字", 1));
+        }
+        fans
+    }
+}
+
+impl Ruleset for ChineseOfficialRules {
+    fn is_winning(&self, tiles: &[TileId]) -> bool {
+        Self::fans(tiles).iter().map(|(_, v)| v).sum::<u32>() >= MCR_MIN_FAN
+    }
+
+    fn evaluate(&self, tiles: &[TileId], levels: &PatternLevels) -> HandResult {
+        let fans = Self::fans(tiles);
+        let total: u32 = fans.iter().map(|(_, v)| v).sum();
+        if fans.is_empty() {
+            return no_win(tiles, None);
+        }
+        if total < MCR_MIN_FAN {
+            return no_win(tiles, Some((total, MCR_MIN_FAN)));
+        }
+        fan_result(tiles, levels, &fans, total / 2)
+    }
+}
+
+// ============ Hong Kong (港式) ============
+
+/// 港式: one faan to win
+const HK_MIN_FAAN: u32 = 1;
+/// 爆棚: hands are capped at the limit
+const HK_LIMIT_FAAN: u32 = 13;
+
+/// Hong Kong rules: only four melds and a pair (or 十三幺) win,
+/// no 七对, and the mult is twice the faan
+pub struct HongKongRules;
+
+impl HongKongRules {
+    fn faan(tiles: &[TileId]) -> Vec<Fan> {
+        let counts = build_count_array(tiles);
+        if check_thirteen_orphans(&counts) {
+            return vec![("十三幺", HK_LIMIT_FAAN)];
+        }
+        if !check_standard(&counts) {
+            return Vec::new();
+        }
+        let Some(melds) = decompose_hand(tiles) else {
+            return Vec::new();
+        };
+
+        let mut faan = Vec::new();
+        if check_chinitsu(tiles) {
+            faan.push(("清一色", 7));
+        } else if check_honitsu(tiles) {
+            faan.push(("混一色", 3));
+        }
+        if check_toitoi(&counts) {
+            faan.push(("对对和", 3));
+        }
+        if is_all_sequences(&melds) {
+            faan.push(("平和", 1));
+        }
+        let dragons = melds.iter().filter(|m| is_triplet(m) && m.first.suit == TileSuit::Dragon);
+        faan.extend(dragons.map(|_| ("箭刻", 1)));
+        faan
+    }
+
+    fn total(faan: &[Fan]) -> u32 {
+        faan.iter().map(|(_, v)| v).sum::<u32>().min(HK_LIMIT_FAAN)
+    }
+}
+
+impl Ruleset for HongKongRules {
+    fn is_winning(&self, tiles: &[TileId]) -> bool {
+        Self::total(&Self::faan(tiles)) >= HK_MIN_FAAN
+    }
+
+    fn evaluate(&self, tiles: &[TileId], levels: &PatternLevels) -> HandResult {
+        let faan = Self::faan(tiles);
+        let total = Self::total(&faan);
+        if faan.is_empty() && !check_standard(&build_count_array(tiles)) {
+            return no_win(tiles, None);
+        }
+        if total < HK_MIN_FAAN {
+            return no_win(tiles, Some((total, HK_MIN_FAAN)));
+        }
+        fan_result(tiles, levels, &faan, 2 * total)
+    }
+}
+
+// ============ Sichuan (四川血战) ============
+
+/// 四川麻将: 108 tiles with no honors. Four melds and a pair or 七对 win,
+/// and every fan doubles the mult.
+pub struct SichuanRules;
+
+impl SichuanRules {
+    fn fans(tiles: &[TileId]) -> Vec<Fan> {
+        if tiles.iter().any(|t| t.is_honor()) {
+            return Vec::new();
+        }
+        let counts = build_count_array(tiles);
+        // Four of a kind counts as two pairs
+        let seven_pairs = tiles.len() == 14 && counts.iter().all(|&c| c % 2 == 0);
+        if !seven_pairs && !check_standard(&counts) {
+            return Vec::new();
+        }
+        let mut roots = counts.iter().filter(|&&c| c == 4).count();
+
+        let mut fans = vec![("平胡", 0)];
+        if seven_pairs && roots > 0 {
+            fans.push(("龙七对", 3));
+            roots -= 1;
+        } else if seven_pairs {
+            fans.push(("七对", 2));
+        } else if check_toitoi(&counts) {
+            fans.push(("对对和", 1));
+        }
+        if check_chinitsu(tiles) {
+            fans.push(("清一色", 2));
+        }
+        fans.extend(std::iter::repeat_n(("根", 1), roots));
+        fans
+    }
+}
+
+impl Ruleset for SichuanRules {
+    fn generate_deck(&self) -> Vec<Tile> {
+        Tile::generate_full_set()
+            .into_iter()
+            .filter(|t| !t.id.is_honor())
+            .collect()
+    }
+
+    fn is_winning(&self, tiles: &[TileId]) -> bool {
+        !Self::fans(tiles).is_empty()
+    }
+
+    fn evaluate(&self, tiles: &[TileId], levels: &PatternLevels) -> HandResult {
+        let fans = Self::fans(tiles);
+        if fans.is_empty() {
+            return no_win(tiles, None);
+        }
+        let total: u32 = fans.iter().map(|(_, v)| v).sum();
+        fan_result(tiles, levels, &fans, 1 << total)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_tiles(specs: &[(TileSuit, u8, u8)]) -> Vec<TileId> {
+        specs
+            .iter()
+            .flat_map(|&(suit, value, count)| (0..count).map(move |_| TileId { suit, value }))
+            .collect()
+    }
+
+    fn chicken_hand() -> Vec<TileId> {
+        make_tiles(&[
+            (TileSuit::Man, 1, 3),
+            (TileSuit::Pin, 2, 1), (TileSuit::Pin, 3, 1), (TileSuit::Pin, 4, 1),
+            (TileSuit::Sou, 5, 1), (TileSuit::Sou, 6, 1), (TileSuit::Sou, 7, 1),
+            (TileSuit::Man, 7, 1), (TileSuit::Man, 8, 1), (TileSuit::Man, 9, 1),
+            (TileSuit::Pin, 5, 2),
+        ])
+    }
+
+    fn flush_hand() -> Vec<TileId> {
+        make_tiles(&[
+            (TileSuit::Man, 1, 2),
+            (TileSuit::Man, 2, 1), (TileSuit::Man, 3, 1), (TileSuit::Man, 4, 2),
+            (TileSuit::Man, 5, 1), (TileSuit::Man, 6, 2), (TileSuit::Man, 7, 2),
+            (TileSuit::Man, 8, 2), (TileSuit::Man, 9, 1),
+        ])
+    }
+
+    fn seven_pairs() -> Vec<TileId> {
+        make_tiles(&[
+            (TileSuit::Man, 1, 2), (TileSuit::Man, 3, 2),
+            (TileSuit::Pin, 5, 2), (TileSuit::Pin, 7, 2),
+            (TileSuit::Sou, 2, 2), (TileSuit::Wind, 1, 2),
+            (TileSuit::Dragon, 1, 2),
+        ])
+    }
+
+    #[test]
+    fn test_fan_floors() {
+        let levels = PatternLevels::default();
+        let chicken = chicken_hand();
+        assert!(RulesetKind::Riichi.rules().is_winning(&chicken));
+        for kind in [RulesetKind::ChineseOfficial, RulesetKind::HongKong] {
+            assert!(!kind.rules().is_winning(&chicken));
+            let result = kind.rules().evaluate(&chicken, &levels);
+            assert_eq!(result.pattern, HandPattern::NoWin);
+            assert!(result.pattern_name.contains("未满"));
+        }
+
+        // 清一色 + 平和 clears both floors
+        let flush = flush_hand();
+        let mcr = RulesetKind::ChineseOfficial.rules().evaluate(&flush, &levels);
+        assert!(mcr.pattern.is_winning());
+        assert!(mcr.pattern_name.contains("清一色"));
+        let hk = RulesetKind::HongKong.rules().evaluate(&flush, &levels);
+        assert_eq!(hk.pattern_name, "清一色+平和 8番");
+        assert_eq!(hk.multiplier, 16);
+    }
+
+    #[test]
+    fn test_seven_pairs_by_ruleset() {
+        let pairs = seven_pairs();
+        assert!(RulesetKind::Riichi.rules().is_winning(&pairs));
+        assert!(RulesetKind::ChineseOfficial.rules().is_winning(&pairs));
+        assert!(!RulesetKind::HongKong.rules().is_winning(&pairs));
+
+        // Waiting on the last pair is only tenpai where 七对 wins
+        let (board, hand) = pairs.split_at(9);
+        let hand = &hand[..4];
+        assert!(can_reach_tenpai(board, hand, RulesetKind::Riichi.rules()));
+        assert!(!can_reach_tenpai(board, hand, RulesetKind::HongKong.rules()));
+    }
+
+    #[test]
+    fn test_sichuan() {
+        let levels = PatternLevels::default();
+        let rules = RulesetKind::Sichuan.rules();
+        assert_eq!(rules.generate_deck().len(), 108);
+
+        // Honors never win
+        assert!(!rules.is_winning(&seven_pairs()));
+
+        // 龙七对 uses up one 根; the other quad still counts
+        let dragon_pairs = make_tiles(&[
+            (TileSuit::Man, 1, 4), (TileSuit::Man, 3, 4),
+            (TileSuit::Man, 5, 2), (TileSuit::Man, 7, 2),
+            (TileSuit::Man, 9, 2),
+        ]);
+        let result = rules.evaluate(&dragon_pairs, &levels);
+        assert_eq!(result.pattern_name, "平胡+龙七对+清一色+根 6番");
+        assert_eq!(result.multiplier, 64);
+        assert!(result.winning);
+        assert!(result.pattern.is_winning());
+
+        // The pipeline scores it as a win, tiles and all
+        let scored: Vec<ScoredTile> = dragon_pairs
+            .iter()
+            .map(|&id| ScoredTile { id, enhancement: None, play: 0 })
+            .collect();
+        let context = HandContext { ruleset: RulesetKind::Sichuan, ..default() };
+        let breakdown = score_hand(&scored, &context, &levels, &[]);
+        assert!(breakdown.hand.winning);
+        let tile_steps = breakdown
+            .steps
+            .iter()
+            .filter(|step| matches!(step.source, ScoreSource::Tile { .. }))
+            .count();
+        assert_eq!(tile_steps, dragon_pairs.len());
+        assert!(breakdown.chips > result.base);
+
+        // Holding the void suit is 花猪
+        assert!(void_broken(&flush_hand(), TileSuit::Pin).is_none());
+        let pig = void_broken(&flush_hand(), TileSuit::Man).unwrap();
+        assert_eq!(pig.pattern, HandPattern::NoWin);
+        assert!(!pig.winning);
+
+        // ...so 定缺 also rules out winning and tenpai on that suit
+        let voided = RulesetKind::Sichuan.rules_for(Some(TileSuit::Man));
+        assert!(!voided.is_winning(&dragon_pairs));
+        assert!(!voided.evaluate(&dragon_pairs, &levels).winning);
+        assert!(RulesetKind::Sichuan.rules_for(Some(TileSuit::Pin)).is_winning(&dragon_pairs));
+        let flush = flush_hand();
+        let (board, hand) = flush.split_at(12);
+        assert!(can_reach_tenpai(board, &hand[..1], rules));
+        assert!(!can_reach_tenpai(board, &hand[..1], &voided));
+    }
+}
@@ -1,45 +1,188 @@
 use bevy::prelude::*;
+use bevy::sprite::Anchor;
 
-use crate::components::board::HandPatternText;
+use crate::components::board::{HandPatternText, MeldLabel};
 use crate::components::tile::*;
 use crate::events::*;
+use crate::plugins::board::check_declared_play;
 use crate::plugins::game::{AppState, PlayPhase};
+use crate::plugins::ruleset::{Ruleset, RulesetKind};
+use crate::plugins::tile::{meld_centers, BOARD_Y, TILE_HEIGHT};
 use crate::resources::*;
+use crate::score::Score;
 
 pub struct ScoringPlugin;
 
 impl Plugin for ScoringPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(
-            OnEnter(PlayPhase::Scoring),
-            calculate_score,
-        )
-        .add_systems(
-            OnEnter(PlayPhase::RoundResult),
-            evaluate_round_result,
-        );
+        app.init_resource::<PatternLevels>()
+            .init_resource::<Jokers>()
+            .init_resource::<ScoreAnimation>()
+            .init_resource::<ScoreAnimationSpeed>()
+            .init_resource::<SelectionPreview>()
+            .add_systems(
+                OnEnter(PlayPhase::Scoring),
+                (calculate_score, show_scored_hand).chain(),
+            )
+            .add_systems(
+                Update,
+                (
+                    animate_score.run_if(in_state(PlayPhase::Scoring)),
+                    (mark_preview_dirty, preview_selection)
+                        .chain()
+                        .run_if(in_state(PlayPhase::Selecting)),
+                ),
+            )
+            .add_systems(OnExit(PlayPhase::Selecting), clear_preview)
+            .add_systems(
+                OnExit(PlayPhase::Scoring),
+                (reset_tile_highlight, despawn_meld_labels),
+            )
+            .add_systems(OnEnter(PlayPhase::RoundResult), evaluate_round_result)
+            .add_observer(on_round_ended);
     }
 }
 
 // ============ Hand patterns ============
 
-#[derive(Debug, Clone)]
-pub struct HandResult {
-    pub pattern_name: String,
-    pub base: u32,
-    pub multiplier: u32,
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum HandPattern {
+    ThirteenOrphans, // 国士无双
+    FullFlush,       // 清一色
+    HalfFlush,       // 混一色
+    SevenPairs,      // 七对子
+    AllTriplets,     // 对对和
+    PureStraight,    // 一气通贯
+    AllSimples,      // 断幺九
+    Pinfu,           // 平和
+    Standard,        // 和了
+    NoWin,           // 未和牌
+    #[default]
+    Empty,           // 无牌型
 }
 
-impl HandResult {
-    pub fn total(&self) -> u32 {
-        self.base * self.multiplier
+impl HandPattern {
+    /// Patterns that can be levelled up with pattern cards
+    pub const UPGRADABLE: [HandPattern; 9] = [
+        HandPattern::ThirteenOrphans,
+        HandPattern::FullFlush,
+        HandPattern::HalfFlush,
+        HandPattern::SevenPairs,
+        HandPattern::AllTriplets,
+        HandPattern::PureStraight,
+        HandPattern::AllSimples,
+        HandPattern::Pinfu,
+        HandPattern::Standard,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            HandPattern::ThirteenOrphans => "国士无双",
+            HandPattern::FullFlush => "清一色",
+            HandPattern::HalfFlush => "混一色",
+            HandPattern::SevenPairs => "七对子",
+            HandPattern::AllTriplets => "对对和",
+            HandPattern::PureStraight => "一气通贯",
+            HandPattern::AllSimples => "断幺九",
+            HandPattern::Pinfu => "平和",
+            HandPattern::Standard => "和了",
+            HandPattern::NoWin => "未和牌",
+            HandPattern::Empty => "无牌型",
+        }
+    }
+
+    /// What the hand needs to count as this pattern
+    pub fn description(&self) -> &'static str {
+        match self {
+            HandPattern::ThirteenOrphans => "十三种幺九牌各一张, 其中一种成对",
+            HandPattern::FullFlush => "和牌且全部是同一花色的数牌",
+            HandPattern::HalfFlush => "和牌且只有一种花色的数牌加字牌",
+            HandPattern::SevenPairs => "七个不同的对子",
+            HandPattern::AllTriplets => "四组刻子加一对雀头",
+            HandPattern::PureStraight => "同一花色 123 456 789 三组顺子",
+            HandPattern::AllSimples => "和牌且没有幺九牌和字牌",
+            HandPattern::Pinfu => "四组顺子加一对非役牌雀头",
+            HandPattern::Standard => "四组面子加一对雀头",
+            HandPattern::NoWin => "出牌区的牌没有组成和牌",
+            HandPattern::Empty => "出牌区还没有牌",
+        }
+    }
+
+    /// Base chips at level 1
+    pub fn base(&self) -> u32 {
+        match self {
+            HandPattern::Empty | HandPattern::NoWin => 0,
+            _ => 10,
+        }
+    }
+
+    /// Limit hands (役満), the biggest a pattern gets whatever its level
+    pub fn is_limit(&self) -> bool {
+        matches!(self, HandPattern::ThirteenOrphans)
     }
+
+    /// Multiplier at level 1
+    pub fn multiplier(&self) -> u32 {
+        match self {
+            HandPattern::ThirteenOrphans => 13,
+            HandPattern::FullFlush => 8,
+            HandPattern::HalfFlush => 5,
+            HandPattern::SevenPairs | HandPattern::AllTriplets => 4,
+            HandPattern::PureStraight => 3,
+            HandPattern::AllSimples => 2,
+            _ => 1,
+        }
+    }
+
+    /// (base, multiplier) gained per level above 1
+    pub fn level_bonus(&self) -> (u32, u32) {
+        match self {
+            HandPattern::ThirteenOrphans => (30, 4),
+            HandPattern::FullFlush => (20, 3),
+            HandPattern::HalfFlush => (15, 2),
+            HandPattern::SevenPairs | HandPattern::AllTriplets => (10, 1),
+            HandPattern::PureStraight | HandPattern::AllSimples => (10, 1),
+            HandPattern::Pinfu | HandPattern::Standard => (5, 1),
+            HandPattern::NoWin | HandPattern::Empty => (0, 0),
+        }
+    }
+
+    /// (base, multiplier) of this pattern at the given level
+    pub fn leveled(&self, level: u32) -> (u32, u32) {
+        let (base_bonus, mult_bonus) = self.level_bonus();
+        (
+            self.base() + base_bonus * (level - 1),
+            self.multiplier() + mult_bonus * (level - 1),
+        )
+    }
+
+    pub fn is_winning(&self) -> bool {
+        !matches!(self, HandPattern::NoWin | HandPattern::Empty)
+    }
+
+    pub fn is_upgradable(&self) -> bool {
+        Self::UPGRADABLE.contains(self)
+    }
+}
+
+/// The recognised pattern and the chips/mult it starts scoring from,
+/// before any tile or modifier has contributed
+#[derive(Debug, Clone, Default)]
+pub struct HandResult {
+    pub pattern_name: String,
+    pub pattern: HandPattern,
+    /// A legal win under the ruleset it was evaluated with.
+    /// `pattern` is only the closest default pattern, used for levels.
+    pub winning: bool,
+    pub level: u32,
+    pub base: Score,
+    pub multiplier: Score,
 }
 
-/// Build a [u8; 34] count array from tile IDs
-fn build_count_array(tiles: &[TileId]) -> [u8; 34] {
+/// Build a [u8; 34] count array from tile IDs; bonus tiles aren't counted
+pub fn build_count_array(tiles: &[TileId]) -> [u8; 34] {
     let mut counts = [0u8; 34];
-    for tile in tiles {
+    for tile in tiles.iter().filter(|t| !t.is_bonus()) {
         counts[tile.to_index()] += 1;
     }
     counts
@@ -47,7 +190,7 @@ fn build_count_array(tiles: &[TileId]) -> [u8; 34] {
 
 /// Check for standard winning hand: 1 pair + N melds (sets of 3)
 /// A meld is either a triplet (3 same) or a sequence (3 consecutive in same suit)
-fn check_standard(counts: &[u8; 34]) -> bool {
+pub fn check_standard(counts: &[u8; 34]) -> bool {
     // Try each possible pair
     for pair_idx in 0..34 {
         if counts[pair_idx] < 2 {
@@ -116,7 +259,7 @@ fn remove_melds(counts: &mut [u8; 34]) -> bool {
 }
 
 /// Check for 7 pairs (七对子)
-fn check_seven_pairs(counts: &[u8; 34]) -> bool {
+pub fn check_seven_pairs(counts: &[u8; 34]) -> bool {
     let pairs = counts.iter().filter(|&&c| c >= 2).count();
     let total: u8 = counts.iter().sum();
     pairs == 7 && total == 14
@@ -124,7 +267,7 @@ fn check_seven_pairs(counts: &[u8; 34]) -> bool {
 
 /// Check for Thirteen Orphans (国士无双)
 /// Requires one of each: 1m,9m,1p,9p,1s,9s,東,南,西,北,中,發,白  plus one duplicate
-fn check_thirteen_orphans(counts: &[u8; 34]) -> bool {
+pub fn check_thirteen_orphans(counts: &[u8; 34]) -> bool {
     let orphan_indices = [
         0, 8,     // 1m, 9m
         9, 17,    // 1p, 9p
@@ -153,13 +296,13 @@ fn check_thirteen_orphans(counts: &[u8; 34]) -> bool {
 }
 
 /// Check 断幺九 (tanyao) - all tiles are simples (2-8 of numbered suits)
-fn check_tanyao(tiles: &[TileId]) -> bool {
+pub fn check_tanyao(tiles: &[TileId]) -> bool {
     tiles.iter().all(|t| t.is_simple())
 }
 
 /// Check 平和 (pinfu) - standard hand with all sequences + no value pair
 /// (Simplified: standard hand with no triplets)
-fn check_pinfu(counts: &[u8; 34]) -> bool {
+pub fn check_pinfu(counts: &[u8; 34]) -> bool {
     // Must be a standard hand first
     if !check_standard(counts) {
         return false;
@@ -170,7 +313,7 @@ fn check_pinfu(counts: &[u8; 34]) -> bool {
 }
 
 /// Check 一気通貫 (ikkitsukan / 一气通贯) - 123, 456, 789 of one suit
-fn check_straight(counts: &[u8; 34]) -> bool {
+pub fn check_straight(counts: &[u8; 34]) -> bool {
     for suit_start in [0, 9, 18] {
         // Check: 1,2,3,4,5,6,7,8,9 all present
         let has_straight = (0..9).all(|i| counts[suit_start + i] >= 1);
@@ -182,7 +325,7 @@ fn check_straight(counts: &[u8; 34]) -> bool {
 }
 
 /// Check 対対和 (toitoi / 对对和) - all triplets, no sequences
-fn check_toitoi(counts: &[u8; 34]) -> bool {
+pub fn check_toitoi(counts: &[u8; 34]) -> bool {
     if !check_standard(counts) {
         return false;
     }
@@ -202,7 +345,7 @@ fn check_toitoi(counts: &[u8; 34]) -> bool {
 }
 
 /// Check 混一色 (honitsu) - all tiles from one suit + honor tiles only
-fn check_honitsu(tiles: &[TileId]) -> bool {
+pub fn check_honitsu(tiles: &[TileId]) -> bool {
     let has_honors = tiles.iter().any(|t| t.is_honor());
     if !has_honors {
         return false;
@@ -220,7 +363,7 @@ fn check_honitsu(tiles: &[TileId]) -> bool {
 }
 
 /// Check 清一色 (chinitsu) - all tiles from a single numbered suit
-fn check_chinitsu(tiles: &[TileId]) -> bool {
+pub fn check_chinitsu(tiles: &[TileId]) -> bool {
     if tiles.is_empty() {
         return false;
     }
@@ -231,261 +374,1500 @@ fn check_chinitsu(tiles: &[TileId]) -> bool {
     tiles.iter().all(|t| t.suit == first)
 }
 
-/// Evaluate the best hand pattern from a set of tile IDs
-pub fn evaluate_hand(tiles: &[TileId]) -> HandResult {
-    if tiles.is_empty() {
-        return HandResult {
-            pattern_name: "无牌型".into(),
-            base: 0,
-            multiplier: 1,
-        };
+// ============ Hand decomposition ============
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MeldKind {
+    Pair,     // 雀头 / 对子
+    Sequence, // 顺子
+    Triplet,  // 刻子
+    Kong,     // 杠子: only ever declared, counts as a triplet for patterns
+}
+
+impl MeldKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            MeldKind::Pair => "雀头",
+            MeldKind::Sequence => "顺子",
+            MeldKind::Triplet => "刻子",
+            MeldKind::Kong => "杠子",
+        }
     }
+}
 
-    let counts = build_count_array(tiles);
-    let is_standard = check_standard(&counts);
+/// A group of tiles in a winning hand, identified by its lowest tile
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Meld {
+    pub kind: MeldKind,
+    pub first: TileId,
+}
 
-    // Check from highest to lowest value patterns
-    // 国士无双 ×13
-    if check_thirteen_orphans(&counts) {
-        return HandResult {
-            pattern_name: "国士无双".into(),
-            base: 10,
-            multiplier: 13,
-        };
+impl Meld {
+    pub fn tiles(&self) -> Vec<TileId> {
+        match self.kind {
+            MeldKind::Pair => vec![self.first; 2],
+            MeldKind::Triplet => vec![self.first; 3],
+            MeldKind::Kong => vec![self.first; 4],
+            MeldKind::Sequence => (0..3)
+                .map(|i| TileId {
+                    suit: self.first.suit,
+                    value: self.first.value + i,
+                })
+                .collect(),
+        }
     }
+}
 
-    // 清一色 ×8
-    if is_standard && check_chinitsu(tiles) {
-        return HandResult {
-            pattern_name: "清一色".into(),
-            base: 10,
-            multiplier: 8,
-        };
+/// Like `remove_melds`, but records the melds it removes
+fn collect_melds(counts: &mut [u8; 34], melds: &mut Vec<Meld>) -> bool {
+    let first = match counts.iter().position(|&c| c > 0) {
+        Some(idx) => idx,
+        None => return true,
+    };
+
+    if counts[first] >= 3 {
+        counts[first] -= 3;
+        melds.push(Meld {
+            kind: MeldKind::Triplet,
+            first: TileId::from_index(first),
+        });
+        if collect_melds(counts, melds) {
+            return true;
+        }
+        melds.pop();
+        counts[first] += 3;
     }
 
-    // 混一色 ×5
-    if is_standard && check_honitsu(tiles) {
-        return HandResult {
-            pattern_name: "混一色".into(),
-            base: 10,
-            multiplier: 5,
-        };
+    // Sequences never start past 7 or on an honor
+    let pos_in_suit = first % 9;
+    if first < 27 && pos_in_suit <= 6 && counts[first + 1] >= 1 && counts[first + 2] >= 1 {
+        for count in &mut counts[first..first + 3] {
+            *count -= 1;
+        }
+        melds.push(Meld {
+            kind: MeldKind::Sequence,
+            first: TileId::from_index(first),
+        });
+        if collect_melds(counts, melds) {
+            return true;
+        }
+        melds.pop();
+        for count in &mut counts[first..first + 3] {
+            *count += 1;
+        }
+    }
+
+    false
+}
+
+/// Split a winning hand into its pair and melds, or into 7 pairs.
+/// Returns `None` for hands without a meld structure (国士无双, no win).
+pub fn decompose_hand(tiles: &[TileId]) -> Option<Vec<Meld>> {
+    let counts = build_count_array(tiles);
+
+    for pair_idx in 0..34 {
+        if counts[pair_idx] < 2 {
+            continue;
+        }
+        let mut remaining = counts;
+        remaining[pair_idx] -= 2;
+        let mut melds = vec![Meld {
+            kind: MeldKind::Pair,
+            first: TileId::from_index(pair_idx),
+        }];
+        if collect_melds(&mut remaining, &mut melds) {
+            return Some(melds);
+        }
     }
 
-    // 七对子 ×4
     if check_seven_pairs(&counts) {
-        return HandResult {
-            pattern_name: "七对子".into(),
-            base: 10,
-            multiplier: 4,
-        };
+        let pairs = (0..34)
+            .filter(|&i| counts[i] == 2)
+            .map(|i| Meld {
+                kind: MeldKind::Pair,
+                first: TileId::from_index(i),
+            })
+            .collect();
+        return Some(pairs);
     }
 
-    // 对对和 ×4
-    if check_toitoi(&counts) {
-        return HandResult {
-            pattern_name: "对对和".into(),
-            base: 10,
-            multiplier: 4,
-        };
+    None
+}
+
+/// For each tile, the index of the meld it belongs to
+pub fn assign_meld_groups(tiles: &[TileId], melds: &[Meld]) -> Vec<Option<usize>> {
+    let mut groups = vec![None; tiles.len()];
+    for (meld_index, meld) in melds.iter().enumerate() {
+        for id in meld.tiles() {
+            if let Some(slot) = (0..tiles.len()).find(|&i| groups[i].is_none() && tiles[i] == id) {
+                groups[slot] = Some(meld_index);
+            }
+        }
     }
+    groups
+}
 
-    // 一气通贯 ×3
-    if is_standard && check_straight(&counts) {
-        return HandResult {
-            pattern_name: "一气通贯".into(),
-            base: 10,
-            multiplier: 3,
-        };
+/// Whether `meld` is part of what makes `pattern`, e.g. the triplets of 对对和
+/// or the three sequences of 一气通贯
+pub fn meld_contributes(pattern: HandPattern, meld: &Meld, melds: &[Meld]) -> bool {
+    match pattern {
+        HandPattern::FullFlush
+        | HandPattern::HalfFlush
+        | HandPattern::SevenPairs
+        | HandPattern::AllSimples
+        | HandPattern::Pinfu => true,
+        HandPattern::AllTriplets => matches!(meld.kind, MeldKind::Triplet | MeldKind::Kong),
+        HandPattern::PureStraight => {
+            let in_straight = |m: &Meld, value: u8| {
+                m.kind == MeldKind::Sequence && m.first.suit == meld.first.suit && m.first.value == value
+            };
+            [1, 4, 7].contains(&meld.first.value)
+                && [1, 4, 7].iter().all(|&v| melds.iter().any(|m| in_straight(m, v)))
+                && in_straight(meld, meld.first.value)
+        }
+        _ => false,
     }
+}
 
-    // 断幺九 ×2
-    if is_standard && check_tanyao(tiles) {
-        return HandResult {
-            pattern_name: "断幺九".into(),
-            base: 10,
-            multiplier: 2,
-        };
+/// The meld a set of played tiles declares, if they form exactly one
+pub fn declare_meld(tiles: &[TileId]) -> Option<Meld> {
+    let mut sorted = tiles.to_vec();
+    sorted.sort();
+    let first = *sorted.first()?;
+    let all_same = sorted.iter().all(|&t| t == first);
+    let kind = match sorted.len() {
+        2 if all_same => MeldKind::Pair,
+        3 if all_same => MeldKind::Triplet,
+        4 if all_same => MeldKind::Kong,
+        3 if !first.is_honor()
+            && sorted.iter().all(|t| t.suit == first.suit)
+            && sorted[1].value == first.value + 1
+            && sorted[2].value == first.value + 2 =>
+        {
+            MeldKind::Sequence
+        }
+        _ => return None,
+    };
+    Some(Meld { kind, first })
+}
+
+/// Tiles as the pattern evaluator sees them: each declared kong counts as a triplet
+fn evaluated_ids(tiles: &[ScoredTile], declared: &[Meld]) -> Vec<TileId> {
+    let mut ids: Vec<TileId> = tiles.iter().map(|t| t.id).collect();
+    for meld in declared.iter().filter(|m| m.kind == MeldKind::Kong) {
+        if let Some(i) = ids.iter().position(|&id| id == meld.first) {
+            ids.remove(i);
+        }
     }
+    ids
+}
 
-    // 平和 ×1
-    if check_pinfu(&counts) {
-        return HandResult {
-            pattern_name: "平和".into(),
-            base: 10,
-            multiplier: 1,
-        };
+// ============ Winning tile and wait ============
+
+/// Shape of the wait the winning tile completed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WaitShape {
+    Ryanmen, // 两面: 23 waiting on 1 or 4
+    Kanchan, // 嵌张: 13 waiting on 2
+    Penchan, // 边张: 12 waiting on 3, 89 waiting on 7
+    Tanki,   // 单骑: waiting on the pair
+    Shanpon, // 双碰: two pairs, either becomes a triplet
+}
+
+impl WaitShape {
+    pub fn label(&self) -> &'static str {
+        match self {
+            WaitShape::Ryanmen => "两面",
+            WaitShape::Kanchan => "嵌张",
+            WaitShape::Penchan => "边张",
+            WaitShape::Tanki => "单骑",
+            WaitShape::Shanpon => "双碰",
+        }
     }
 
-    // Standard win (no special pattern)
-    if is_standard {
-        return HandResult {
-            pattern_name: "和了".into(),
-            base: 10,
-            multiplier: 1,
-        };
+    /// Narrower waits are harder to win on and score more
+    pub fn bonus_chips(&self) -> u32 {
+        match self {
+            WaitShape::Ryanmen => 0,
+            WaitShape::Shanpon => 10,
+            WaitShape::Kanchan | WaitShape::Penchan | WaitShape::Tanki => 20,
+        }
     }
+}
 
-    // No winning pattern – give a small consolation score based on tile count
-    HandResult {
-        pattern_name: "未和牌".into(),
-        base: tiles.len() as u32,
-        multiplier: 1,
+/// Wait shape `winning` completed within `meld`, if it is part of it
+fn wait_in_meld(meld: &Meld, winning: TileId) -> Option<WaitShape> {
+    if !meld.tiles().contains(&winning) {
+        return None;
     }
+    let shape = match meld.kind {
+        MeldKind::Pair => WaitShape::Tanki,
+        MeldKind::Triplet | MeldKind::Kong => WaitShape::Shanpon,
+        MeldKind::Sequence => match winning.value - meld.first.value {
+            1 => WaitShape::Kanchan,
+            0 if meld.first.value == 7 => WaitShape::Penchan,
+            2 if meld.first.value == 1 => WaitShape::Penchan,
+            _ => WaitShape::Ryanmen,
+        },
+    };
+    Some(shape)
 }
 
-// ============ Bevy Systems ============
+/// Best-scoring wait the winning tile can be read as in this decomposition
+pub fn classify_wait(melds: &[Meld], winning: TileId) -> Option<WaitShape> {
+    melds
+        .iter()
+        .filter_map(|meld| wait_in_meld(meld, winning))
+        .max_by_key(|shape| shape.bonus_chips())
+}
 
-fn calculate_score(
-    mut commands: Commands,
-    board: Res<PlayBoard>,
-    tile_q: Query<&Tile>,
-    mut game_state: ResMut<GameState>,
-    mut pattern_text_q: Query<&mut Text, With<HandPatternText>>,
-) {
-    // Gather tile IDs from board
-    let tile_ids: Vec<TileId> = board
-        .tiles
+/// Pick the winning tile among the tiles of the last play. When that play held
+/// several tiles the hand reads as the easiest wait, so a hard wait only pays
+/// off when its tile is played on its own. Returns the tile's index and wait.
+pub fn find_winning_tile(tiles: &[ScoredTile], melds: &[Meld]) -> Option<(usize, WaitShape)> {
+    let last_play = tiles.iter().map(|t| t.play).max()?;
+    tiles
         .iter()
-        .filter_map(|e| tile_q.get(*e).ok())
-        .map(|t| t.id)
+        .enumerate()
+        .filter(|(_, t)| t.play == last_play)
+        .filter_map(|(i, t)| classify_wait(melds, t.id).map(|shape| (i, shape)))
+        .min_by_key(|(_, shape)| shape.bonus_chips())
+}
+
+// ============ Tenpai ============
+
+fn is_winning_counts(counts: &[u8; 34], rules: &dyn Ruleset) -> bool {
+    let tiles: Vec<TileId> = (0..34)
+        .flat_map(|i| std::iter::repeat_n(TileId::from_index(i), counts[i] as usize))
         .collect();
+    rules.is_winning(&tiles)
+}
 
-    let result = evaluate_hand(&tile_ids);
+/// One more tile of some kind would complete the hand (听牌)
+fn is_tenpai_counts(counts: &[u8; 34], rules: &dyn Ruleset) -> bool {
+    (0..34).any(|i| {
+        let mut completed = *counts;
+        completed[i] += 1;
+        counts[i] < 4 && is_winning_counts(&completed, rules)
+    })
+}
 
-    // Update game state
-    game_state.base_ante = result.base;
-    game_state.multiplier = result.multiplier;
-    game_state.current_score += result.total();
+/// Add `needed` more tiles to `counts` from `available`, trying each tile kind
+/// from `from` on, until the result is tenpai
+fn pick_tenpai(
+    counts: &mut [u8; 34],
+    available: &[u8; 34],
+    from: usize,
+    needed: u8,
+    rules: &dyn Ruleset,
+) -> bool {
+    if needed == 0 {
+        return is_tenpai_counts(counts, rules);
+    }
+    if from == 34 {
+        return false;
+    }
+    for take in (0..=available[from].min(needed)).rev() {
+        counts[from] += take;
+        let found = pick_tenpai(counts, available, from + 1, needed - take, rules);
+        counts[from] -= take;
+        if found {
+            return true;
+        }
+    }
+    false
+}
 
-    // Update pattern text
-    if let Ok(mut text) = pattern_text_q.single_mut() {
-        text.0 = format!("{} ({}×{})", result.pattern_name, result.base, result.multiplier);
+/// Whether the board plus some of the hand makes 13 tiles one away from a win under `rules`
+pub fn can_reach_tenpai(board: &[TileId], hand: &[TileId], rules: &dyn Ruleset) -> bool {
+    let Some(needed) = 13usize.checked_sub(board.len()) else {
+        return false;
+    };
+    if needed > hand.len() {
+        return false;
     }
+    let mut counts = build_count_array(board);
+    pick_tenpai(&mut counts, &build_count_array(hand), 0, needed as u8, rules)
+}
 
-    // Trigger score calculated event
-    commands.trigger(ScoreCalculatedEvent {
-        base: result.base,
-        multiplier: result.multiplier,
-        total: result.total(),
-        pattern_name: result.pattern_name,
-    });
+// ============ Shanten ============
 
-    // Transition to RoundResult after a brief delay (immediate for now)
-    commands.trigger(RoundEndedEvent {
-        passed: game_state.current_score >= game_state.target_score,
-    });
+/// Best 2×melds + partial melds (pairs and incomplete sequences) left in
+/// `counts` from `from` on, counting at most four groups in all
+fn group_score(counts: &mut [u8; 34], from: usize, melds: i32, partials: i32) -> i32 {
+    let Some(i) = (from..34).find(|&i| counts[i] > 0) else {
+        return 2 * melds + partials.min(4 - melds);
+    };
+    if melds + partials >= 4 {
+        // No room for more groups; the rest of the tiles can't help
+        return 2 * melds + partials.min(4 - melds);
+    }
+    // Position within the suit, or None for honors which can't run
+    let rank = (i < 27).then_some(i % 9);
+
+    // (tiles taken, melds made, partials made); taking nothing leaves one copy unused
+    let run = [i, i + 1, i + 2];
+    let gap = [i, i + 2];
+    let same = [i; 3];
+    let mut groups: Vec<(&[usize], i32, i32)> = vec![(&[], 0, 0), (&same, 1, 0), (&same[..2], 0, 1)];
+    if rank.is_some_and(|r| r <= 6) {
+        groups.push((&run, 1, 0));
+        groups.push((&gap, 0, 1));
+    }
+    if rank.is_some_and(|r| r <= 7) {
+        groups.push((&run[..2], 0, 1));
+    }
+    groups
+        .into_iter()
+        .filter_map(|(tiles, m, p)| take_group(counts, i, tiles, melds + m, partials + p))
+        .max()
+        .unwrap_or(0)
 }
 
-fn evaluate_round_result(
-    mut game_state: ResMut<GameState>,
-    mut next_app_state: ResMut<NextState<AppState>>,
-    mut next_phase: ResMut<NextState<PlayPhase>>,
-    mut commands: Commands,
-    mut board: ResMut<PlayBoard>,
-) {
-    let passed = game_state.current_score >= game_state.target_score;
+/// Take `tiles` out of `counts` (or just one copy of tile `i` when empty,
+/// leaving it unused) and score the rest
+fn take_group(counts: &mut [u8; 34], i: usize, tiles: &[usize], melds: i32, partials: i32) -> Option<i32> {
+    let tiles = if tiles.is_empty() { &[i][..] } else { tiles };
+    let mut taken = 0;
+    for &t in tiles {
+        if counts[t] == 0 {
+            break;
+        }
+        counts[t] -= 1;
+        taken += 1;
+    }
+    let score = (taken == tiles.len()).then(|| group_score(counts, i, melds, partials));
+    for &t in &tiles[..taken] {
+        counts[t] += 1;
+    }
+    score
+}
 
-    if passed {
-        // Advance to next sub-round
-        game_state.advance_sub_round();
-        game_state.reset_for_sub_round();
+/// Tiles away from a complete hand (向听数): 0 is tenpai, -1 a win.
+/// Takes the best of four melds and a pair, seven pairs and thirteen orphans.
+pub fn shanten(tiles: &[TileId]) -> i32 {
+    let mut counts = build_count_array(tiles);
 
-        // Hide board tiles
-        for &entity in board.tiles.iter() {
-            commands.entity(entity).insert(Visibility::Hidden);
+    let mut regular = 8 - group_score(&mut counts, 0, 0, 0);
+    for i in 0..34 {
+        if counts[i] >= 2 {
+            counts[i] -= 2;
+            regular = regular.min(7 - group_score(&mut counts, 0, 0, 0));
+            counts[i] += 2;
         }
-        board.tiles.clear();
+    }
+
+    let pairs = counts.iter().filter(|&&c| c >= 2).count() as i32;
+    let kinds = counts.iter().filter(|&&c| c > 0).count() as i32;
+    let seven_pairs = 6 - pairs + (7 - kinds).max(0);
+
+    let orphans: Vec<u8> = (0..34)
+        .filter(|&i| TileId::from_index(i).is_terminal() || i >= 27)
+        .map(|i| counts[i])
+        .collect();
+    let orphan_kinds = orphans.iter().filter(|&&c| c > 0).count() as i32;
+    let orphan_pair = orphans.iter().any(|&c| c >= 2) as i32;
+    let thirteen_orphans = 13 - orphan_kinds - orphan_pair;
+
+    regular.min(seven_pairs).min(thirteen_orphans)
+}
 
-        // Return to selecting phase for the next sub-round
-        next_phase.set(PlayPhase::Selecting);
+/// Detect the best hand pattern from a set of tile IDs
+pub fn detect_pattern(tiles: &[TileId]) -> HandPattern {
+    if tiles.is_empty() {
+        return HandPattern::Empty;
+    }
+
+    let counts = build_count_array(tiles);
+    let is_standard = check_standard(&counts);
+
+    // Check from highest to lowest value patterns
+    if check_thirteen_orphans(&counts) {
+        HandPattern::ThirteenOrphans
+    } else if is_standard && check_chinitsu(tiles) {
+        HandPattern::FullFlush
+    } else if is_standard && check_honitsu(tiles) {
+        HandPattern::HalfFlush
+    } else if check_seven_pairs(&counts) {
+        HandPattern::SevenPairs
+    } else if check_toitoi(&counts) {
+        HandPattern::AllTriplets
+    } else if is_standard && check_straight(&counts) {
+        HandPattern::PureStraight
+    } else if is_standard && check_tanyao(tiles) {
+        HandPattern::AllSimples
+    } else if check_pinfu(&counts) {
+        HandPattern::Pinfu
+    } else if is_standard {
+        // Standard win (no special pattern)
+        HandPattern::Standard
     } else {
-        // Failed: go to game over
-        next_app_state.set(AppState::GameOver);
+        HandPattern::NoWin
     }
 }
 
-// ============ Unit Tests ============
+/// Evaluate the best hand pattern from a set of tile IDs, applying the run's pattern levels
+pub fn evaluate_hand(tiles: &[TileId], levels: &PatternLevels) -> HandResult {
+    let pattern = detect_pattern(tiles);
+    let level = levels.level(pattern);
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    let (base, multiplier) = if pattern == HandPattern::NoWin {
+        // No winning pattern – give a small consolation score based on tile count
+        (tiles.len() as u32, 1)
+    } else {
+        pattern.leveled(level)
+    };
 
-    fn make_tiles(specs: &[(TileSuit, u8, u8)]) -> Vec<TileId> {
-        let mut tiles = Vec::new();
-        for &(suit, value, count) in specs {
-            for _ in 0..count {
-                tiles.push(TileId { suit, value });
+    HandResult {
+        pattern_name: pattern.label().into(),
+        pattern,
+        winning: pattern.is_winning(),
+        level,
+        base: base.into(),
+        multiplier: multiplier.into(),
+    }
+}
+
+// ============ Scoring pipeline ============
+
+/// One contribution to the running score.
+/// Effects are applied strictly in the order they are produced, so an
+/// `AddMult` before an `XMult` is worth more than the same pair reversed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScoreEffect {
+    Chips(Score),
+    AddMult(Score),
+    XMult(Score),
+}
+
+impl ScoreEffect {
+    fn apply(&self, chips: &mut Score, mult: &mut Score) {
+        match *self {
+            ScoreEffect::Chips(c) => *chips += c,
+            ScoreEffect::AddMult(m) => *mult += m,
+            ScoreEffect::XMult(x) => *mult *= x,
+        }
+    }
+}
+
+/// A tile taking part in scoring, in board order
+#[derive(Debug, Clone, Copy)]
+pub struct ScoredTile {
+    pub id: TileId,
+    pub enhancement: Option<TileEnhancement>,
+    /// Play of the sub-round that put the tile on the board
+    pub play: u32,
+}
+
+impl ScoredTile {
+    /// Effects of one trigger of this tile: its chips, then its enhancement
+    fn effects(&self) -> Vec<ScoreEffect> {
+        let mut effects = vec![ScoreEffect::Chips(self.id.chips().into())];
+        if let Some(enhancement) = self.enhancement {
+            if enhancement.extra_chips() > 0 {
+                effects.push(ScoreEffect::Chips(enhancement.extra_chips().into()));
+            }
+            if enhancement.extra_mult() > 0 {
+                effects.push(ScoreEffect::AddMult(enhancement.extra_mult().into()));
+            }
+            if enhancement.x_mult() > 1 {
+                effects.push(ScoreEffect::XMult(enhancement.x_mult().into()));
             }
         }
-        tiles
+        effects
     }
+}
 
-    #[test]
-    fn test_standard_win_simple() {
-        // 1m 1m 1m 2m 3m 4m 5m 6m 7m 8m 9m 9m 9m 3p 3p = nope (15 tiles)
-        // Let's do a proper 14-tile hand: 1m×3, 2m,3m,4m, 5m,6m,7m, 8m,9m,9m×3 = 14 nope
-        // Simple: 1m×2 (pair) + 2m,3m,4m + 5m,6m,7m + 1p,2p,3p + 4p,5p,6p = 14
-        let tiles = make_tiles(&[
-            (TileSuit::Man, 1, 2),
-            (TileSuit::Man, 2, 1), (TileSuit::Man, 3, 1), (TileSuit::Man, 4, 1),
-            (TileSuit::Man, 5, 1), (TileSuit::Man, 6, 1), (TileSuit::Man, 7, 1),
-            (TileSuit::Pin, 1, 1), (TileSuit::Pin, 2, 1), (TileSuit::Pin, 3, 1),
-            (TileSuit::Pin, 4, 1), (TileSuit::Pin, 5, 1), (TileSuit::Pin, 6, 1),
-        ]);
-        assert_eq!(tiles.len(), 14);
-        let result = evaluate_hand(&tiles);
-        assert!(result.multiplier >= 1, "Should be a winning hand: {:?}", result);
+/// Something that joins in while a hand is scored, e.g. a joker.
+/// Modifiers run in slot order after each tile's own effects.
+pub trait ScoreModifier {
+    fn name(&self) -> &str;
+
+    /// Effects added every time a tile triggers
+    fn on_tile_scored(&self, _tile: &ScoredTile) -> Vec<ScoreEffect> {
+        Vec::new()
     }
 
-    #[test]
-    fn test_seven_pairs() {
-        let tiles = make_tiles(&[
-            (TileSuit::Man, 1, 2),
-            (TileSuit::Man, 3, 2),
-            (TileSuit::Man, 5, 2),
-            (TileSuit::Man, 7, 2),
-            (TileSuit::Pin, 2, 2),
-            (TileSuit::Pin, 4, 2),
-            (TileSuit::Sou, 6, 2),
-        ]);
-        assert_eq!(tiles.len(), 14);
-        let result = evaluate_hand(&tiles);
-        assert_eq!(result.pattern_name, "七对子");
-        assert_eq!(result.multiplier, 4);
+    /// Extra times a tile is triggered
+    fn retriggers(&self, _tile: &ScoredTile) -> u32 {
+        0
     }
 
-    #[test]
-    fn test_thirteen_orphans() {
-        let tiles = make_tiles(&[
-            (TileSuit::Man, 1, 2), // pair
-            (TileSuit::Man, 9, 1),
-            (TileSuit::Pin, 1, 1),
-            (TileSuit::Pin, 9, 1),
-            (TileSuit::Sou, 1, 1),
-            (TileSuit::Sou, 9, 1),
-            (TileSuit::Wind, 1, 1),
-            (TileSuit::Wind, 2, 1),
-            (TileSuit::Wind, 3, 1),
-            (TileSuit::Wind, 4, 1),
-            (TileSuit::Dragon, 1, 1),
-            (TileSuit::Dragon, 2, 1),
-            (TileSuit::Dragon, 3, 1),
-        ]);
-        assert_eq!(tiles.len(), 14);
-        let result = evaluate_hand(&tiles);
-        assert_eq!(result.pattern_name, "国士无双");
-        assert_eq!(result.multiplier, 13);
+    /// Effects applied once after every tile has scored
+    fn on_hand_scored(&self, _pattern: HandPattern) -> Vec<ScoreEffect> {
+        Vec::new()
     }
+}
 
-    #[test]
-    fn test_chinitsu() {
-        // All man tiles: 1m×3, 2m,3m,4m, 5m,6m,7m, 8m,9m,9m×3 => nah, let me count
-        // 1m×2 (pair) + 1m,2m,3m + 4m,5m,6m + 7m,8m,9m + 7m,8m,9m = 14
-        // 1m×3, 2m×1, 3m×1, 4m×1, 5m×1, 6m×1, 7m×2, 8m×2, 9m×2 = 14
-        let tiles = make_tiles(&[
-            (TileSuit::Man, 1, 3),
-            (TileSuit::Man, 2, 1),
+/// Jokers bought in the shop, each joining the pipeline as a modifier
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JokerKind {
+    Dragon,   // 三元: +mult for every dragon scored
+    Terminal, // 幺九: +chips for every terminal or honor scored
+    Flush,    // 一色: ×mult on 清一色
+    Fives,    // 五福: scores every 5 one extra time
+}
+
+impl JokerKind {
+    pub const ALL: [JokerKind; 4] = [
+        JokerKind::Dragon,
+        JokerKind::Terminal,
+        JokerKind::Flush,
+        JokerKind::Fives,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            JokerKind::Dragon => "三元小丑",
+            JokerKind::Terminal => "幺九小丑",
+            JokerKind::Flush => "一色小丑",
+            JokerKind::Fives => "五福小丑",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            JokerKind::Dragon => "箭牌 +3 倍率",
+            JokerKind::Terminal => "幺九牌 +20 底注",
+            JokerKind::Flush => "清一色 ×3 倍率",
+            JokerKind::Fives => "五额外计分 1 次",
+        }
+    }
+}
+
+impl ScoreModifier for JokerKind {
+    fn name(&self) -> &str {
+        self.label()
+    }
+
+    fn on_tile_scored(&self, tile: &ScoredTile) -> Vec<ScoreEffect> {
+        match self {
+            JokerKind::Dragon if tile.id.suit == TileSuit::Dragon => {
+                vec![ScoreEffect::AddMult(3u32.into())]
+            }
+            JokerKind::Terminal if tile.id.is_terminal() || tile.id.is_honor() => {
+                vec![ScoreEffect::Chips(20u32.into())]
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    fn retriggers(&self, tile: &ScoredTile) -> u32 {
+        u32::from(*self == JokerKind::Fives && tile.id.is_simple() && tile.id.value == 5)
+    }
+
+    fn on_hand_scored(&self, pattern: HandPattern) -> Vec<ScoreEffect> {
+        match self {
+            JokerKind::Flush if pattern == HandPattern::FullFlush => {
+                vec![ScoreEffect::XMult(3u32.into())]
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScoreSource {
+    Pattern,
+    Wait(WaitShape),
+    /// Bonus for winning with every play declared as a meld
+    Declared,
+    Riichi, // 立直
+    Ippatsu, // 一发: won on the play right after riichi
+    Timing(TimingYaku),
+    /// 役牌: a triplet of the round or seat wind
+    Yakuhai(WindRole),
+    /// A revealed flower or season
+    Bonus(TileId),
+    /// All four flowers or all four seasons
+    BonusSet(TileSuit),
+    /// Index into the scored tiles; `trigger` is 0 for the first pass, 1+ for retriggers
+    Tile { index: usize, trigger: u32 },
+    Modifier(String),
+}
+
+/// A single applied effect together with the running totals after it
+#[derive(Debug, Clone)]
+pub struct ScoreStep {
+    pub source: ScoreSource,
+    pub effect: ScoreEffect,
+    pub chips: Score,
+    pub mult: Score,
+}
+
+/// Full record of how a hand was scored, in application order
+#[derive(Debug, Clone, Default)]
+pub struct ScoreBreakdown {
+    pub hand: HandResult,
+    /// Index of the tile that completed the hand, and the wait it filled
+    pub winning: Option<(usize, WaitShape)>,
+    pub steps: Vec<ScoreStep>,
+    pub chips: Score,
+    pub mult: Score,
+}
+
+impl ScoreBreakdown {
+    fn push(&mut self, source: ScoreSource, effect: ScoreEffect) {
+        effect.apply(&mut self.chips, &mut self.mult);
+        self.steps.push(ScoreStep {
+            source,
+            effect,
+            chips: self.chips,
+            mult: self.mult,
+        });
+    }
+
+    pub fn total(&self) -> Score {
+        self.chips * self.mult
+    }
+}
+
+/// +mult per declared meld in a winning hand
+const CLEAN_MELD_MULT: u32 = 1;
+/// Extra chips for each declared kong
+const KONG_CHIPS: u32 = 30;
+/// +mult per 役牌 role of a wind triplet, so a double wind counts twice
+const YAKUHAI_MULT: u32 = 3;
+/// +mult for winning after riichi
+const RIICHI_MULT: u32 = 3;
+/// ×mult for winning on the very next play after riichi
+const IPPATSU_X_MULT: u32 = 2;
+/// +mult per revealed flower or season
+const BONUS_TILE_MULT: u32 = 1;
+/// Extra +mult for the flower or season matching the seat wind (东 = 1)
+const SEAT_BONUS_MULT: u32 = 2;
+/// ×mult for holding all four flowers or all four seasons
+const BONUS_SET_X_MULT: u32 = 2;
+
+/// Yaku earned by when the hand was won rather than its shape
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimingYaku {
+    FirstPlay, // 天和: the whole board in one play
+    LastPlay,  // 河底捞鱼: won on the last play available
+    LastTile,  // 海底捞月: won with the wall's final tile
+    NoDiscards, // 门前清: won without discarding
+}
+
+impl TimingYaku {
+    pub fn label(&self) -> &'static str {
+        match self {
+            TimingYaku::FirstPlay => "天和",
+            TimingYaku::LastPlay => "河底捞鱼",
+            TimingYaku::LastTile => "海底捞月",
+            TimingYaku::NoDiscards => "门前清",
+        }
+    }
+
+    pub fn effect(&self) -> ScoreEffect {
+        match self {
+            TimingYaku::FirstPlay => ScoreEffect::XMult(3u32.into()),
+            TimingYaku::LastPlay => ScoreEffect::AddMult(4u32.into()),
+            TimingYaku::LastTile => ScoreEffect::XMult(2u32.into()),
+            TimingYaku::NoDiscards => ScoreEffect::AddMult(2u32.into()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindRole {
+    Round, // 场风
+    Seat,  // 自风
+}
+
+impl WindRole {
+    pub fn label(&self) -> &'static str {
+        match self {
+            WindRole::Round => "场风",
+            WindRole::Seat => "自风",
+        }
+    }
+}
+
+/// Round and seat wind of the sub-round being scored
+#[derive(Debug, Clone, Copy)]
+pub struct Winds {
+    pub round: TileId,
+    pub seat: TileId,
+}
+
+impl Winds {
+    /// Roles a triplet or kong of this wind plays; both for a double wind
+    pub fn roles(&self, meld: &Meld) -> Vec<WindRole> {
+        if !matches!(meld.kind, MeldKind::Triplet | MeldKind::Kong) {
+            return Vec::new();
+        }
+        [(self.round, WindRole::Round), (self.seat, WindRole::Seat)]
+            .into_iter()
+            .filter(|(wind, _)| *wind == meld.first)
+            .map(|(_, role)| role)
+            .collect()
+    }
+}
+
+/// Play and discard counters for the sub-round being scored
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RoundTiming {
+    pub plays_made: u32,
+    pub plays_remaining: u32,
+    pub discards_made: u32,
+    /// Index of the wall's final tile among the scored tiles, if it was played
+    pub last_wall_tile: Option<usize>,
+}
+
+impl RoundTiming {
+    /// Timing yaku earned by a winning hand of these tiles
+    pub fn yaku(&self, tiles: &[ScoredTile]) -> Vec<TimingYaku> {
+        let last_play = tiles.iter().map(|t| t.play).max().unwrap_or(0);
+        let mut yaku = Vec::new();
+        if self.plays_made == 1 {
+            yaku.push(TimingYaku::FirstPlay);
+        } else if self.plays_remaining == 0 {
+            yaku.push(TimingYaku::LastPlay);
+        }
+        if self
+            .last_wall_tile
+            .is_some_and(|i| tiles.get(i).is_some_and(|t| t.play == last_play))
+        {
+            yaku.push(TimingYaku::LastTile);
+        }
+        if self.discards_made == 0 {
+            yaku.push(TimingYaku::NoDiscards);
+        }
+        yaku
+    }
+}
+
+/// How the sub-round was played, beyond the tiles on the board
+#[derive(Debug, Clone, Default)]
+pub struct HandContext {
+    /// Melds declared in `PlayMode::Declared`, in play order
+    pub declared: Vec<Meld>,
+    /// Plays already on the board when riichi was declared
+    pub riichi: Option<u32>,
+    /// `None` scores the tiles alone, with no timing yaku
+    pub timing: Option<RoundTiming>,
+    pub winds: Option<Winds>,
+    pub ruleset: RulesetKind,
+    /// Sichuan 定缺 suit, which breaks the hand if present
+    pub void_suit: Option<TileSuit>,
+    /// Flowers and seasons revealed this sub-round
+    pub bonus: Vec<TileId>,
+    /// Boss modifier: tiles of this suit are skipped when scoring
+    pub debuffed_suit: Option<TileSuit>,
+}
+
+/// Score a hand step by step:
+/// 1. the pattern's leveled base chips and mult under the run's ruleset
+///    (see `Ruleset::evaluate`),
+///    plus chips for a hard wait on the winning tile, the clean play bonus
+///    when every play was declared as a meld, 役牌 wind triplets,
+///    flowers and seasons, riichi / 一发 and timing yaku
+/// 2. each tile left to right — its chips and enhancement, then every
+///    modifier's per-tile effects — repeated once per retrigger;
+///    tiles of a boss's debuffed suit are skipped
+/// 3. every modifier's hand-level effects, in slot order
+pub fn score_hand(
+    tiles: &[ScoredTile],
+    context: &HandContext,
+    levels: &PatternLevels,
+    modifiers: &[&dyn ScoreModifier],
+) -> ScoreBreakdown {
+    let declared = &context.declared;
+    let ids = evaluated_ids(tiles, declared);
+    let hand = context
+        .ruleset
+        .rules_for(context.void_suit)
+        .evaluate(&ids, levels);
+    let (pattern, winning) = (hand.pattern, hand.winning);
+    let (base, multiplier) = (hand.base, hand.multiplier);
+    let mut breakdown = ScoreBreakdown { hand, ..default() };
+
+    breakdown.push(ScoreSource::Pattern, ScoreEffect::Chips(base));
+    breakdown.push(ScoreSource::Pattern, ScoreEffect::AddMult(multiplier));
+
+    // Tiles only trigger when the ruleset counts the hand as a win
+    if !winning {
+        return breakdown;
+    }
+
+    let melds = if declared.is_empty() {
+        decompose_hand(&ids)
+    } else {
+        Some(declared.to_vec())
+    };
+    let melds = melds.unwrap_or_default();
+    breakdown.winning = find_winning_tile(tiles, &melds);
+    if let Some((_, wait)) = breakdown.winning {
+        if wait.bonus_chips() > 0 {
+            breakdown.push(ScoreSource::Wait(wait), ScoreEffect::Chips(wait.bonus_chips().into()));
+        }
+    }
+
+    if !declared.is_empty() {
+        let kongs = declared.iter().filter(|m| m.kind == MeldKind::Kong).count() as u32;
+        if kongs > 0 {
+            breakdown.push(ScoreSource::Declared, ScoreEffect::Chips((kongs * KONG_CHIPS).into()));
+        }
+        let mult = declared.len() as u32 * CLEAN_MELD_MULT;
+        breakdown.push(ScoreSource::Declared, ScoreEffect::AddMult(mult.into()));
+    }
+
+    if let Some(winds) = context.winds {
+        for role in melds.iter().flat_map(|meld| winds.roles(meld)) {
+            breakdown.push(ScoreSource::Yakuhai(role), ScoreEffect::AddMult(YAKUHAI_MULT.into()));
+        }
+    }
+
+    for &tile in &context.bonus {
+        let seat = context.winds.is_some_and(|w| w.seat.value == tile.value);
+        let mult = BONUS_TILE_MULT + if seat { SEAT_BONUS_MULT } else { 0 };
+        breakdown.push(ScoreSource::Bonus(tile), ScoreEffect::AddMult(mult.into()));
+    }
+    for suit in [TileSuit::Flower, TileSuit::Season] {
+        if context.bonus.iter().filter(|t| t.suit == suit).count() == 4 {
+            breakdown.push(ScoreSource::BonusSet(suit), ScoreEffect::XMult(BONUS_SET_X_MULT.into()));
+        }
+    }
+
+    if let Some(riichi_after) = context.riichi {
+        breakdown.push(ScoreSource::Riichi, ScoreEffect::AddMult(RIICHI_MULT.into()));
+        let last_play = tiles.iter().map(|t| t.play).max().unwrap_or(0);
+        if last_play == riichi_after + 1 {
+            breakdown.push(ScoreSource::Ippatsu, ScoreEffect::XMult(IPPATSU_X_MULT.into()));
+        }
+    }
+
+    if let Some(timing) = context.timing {
+        for yaku in timing.yaku(tiles) {
+            breakdown.push(ScoreSource::Timing(yaku), yaku.effect());
+        }
+    }
+
+    for (index, tile) in tiles.iter().enumerate() {
+        if Some(tile.id.suit) == context.debuffed_suit {
+            continue;
+        }
+        let retriggers = tile.enhancement.map(|e| e.retriggers()).unwrap_or(0)
+            + modifiers.iter().map(|m| m.retriggers(tile)).sum::<u32>();
+        for trigger in 0..=retriggers {
+            for effect in tile.effects() {
+                breakdown.push(ScoreSource::Tile { index, trigger }, effect);
+            }
+            for modifier in modifiers {
+                for effect in modifier.on_tile_scored(tile) {
+                    breakdown.push(ScoreSource::Modifier(modifier.name().into()), effect);
+                }
+            }
+        }
+    }
+
+    for modifier in modifiers {
+        for effect in modifier.on_hand_scored(pattern) {
+            breakdown.push(ScoreSource::Modifier(modifier.name().into()), effect);
+        }
+    }
+
+    breakdown
+}
+
+// ============ Bevy Systems ============
+
+/// Seconds per pipeline step at ×1 speed
+const STEP_SECONDS: f32 = 0.25;
+/// Pause between the last step and slamming the total in
+const SLAM_DELAY_SECONDS: f32 = 0.4;
+/// How long the final score stays up before the round result
+const HOLD_SECONDS: f32 = 0.9;
+
+const SCORING_TILE_SCALE: f32 = 1.25;
+const SCORING_MELD_SCALE: f32 = 1.12;
+const WINNING_TILE_TINT: Color = Color::srgb(1.0, 0.85, 0.4);
+const MELD_LABEL_OFFSET: f32 = 6.0;
+const MELD_LABEL_COLOR: Color = Color::srgb(0.85, 0.85, 0.75);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum AnimationStage {
+    #[default]
+    Steps,
+    Slam,
+    Hold,
+    Done,
+}
+
+/// Playback state of the score breakdown during `PlayPhase::Scoring`
+#[derive(Resource, Debug, Default)]
+pub struct ScoreAnimation {
+    breakdown: ScoreBreakdown,
+    /// Scored tile entities in board order, matching `ScoreSource::Tile::index`
+    tiles: Vec<Entity>,
+    /// Meld index of each scored tile, used to highlight whole melds
+    groups: Vec<Option<usize>>,
+    /// Winning tile and its face color from before it was highlighted
+    winning: Option<(Entity, Color)>,
+    next_step: usize,
+    timer: f32,
+    stage: AnimationStage,
+}
+
+/// What playing the selected tiles would do to the board
+#[derive(Debug, Clone)]
+pub struct PlayPreview {
+    /// Pattern name as the score panel would show it
+    pub heading: String,
+    pub chips: Score,
+    pub mult: Score,
+    pub total: Score,
+    /// 向听 of the board before and after the play
+    pub shanten: (i32, i32),
+    /// The board would hold more than 14 tiles
+    pub over_limit: bool,
+    /// Why the play would be refused, if it would
+    pub rejected: Option<&'static str>,
+}
+
+/// Preview of the current selection, refreshed whenever it changes
+#[derive(Resource, Debug, Default)]
+pub struct SelectionPreview {
+    pub preview: Option<PlayPreview>,
+    /// Selected tiles as of the last change, waiting to be previewed
+    selected: Option<Vec<Entity>>,
+}
+
+/// Pattern name as shown in the score panel, with its level when it can
+/// level up, the wait the hand was won on and any riichi / timing yaku
+fn pattern_heading(breakdown: &ScoreBreakdown) -> String {
+    let hand = &breakdown.hand;
+    let mut heading = if hand.pattern.is_upgradable() {
+        format!("{} Lv.{}", hand.pattern_name, hand.level)
+    } else {
+        hand.pattern_name.clone()
+    };
+    if let Some((_, wait)) = breakdown.winning {
+        heading = format!("{heading} · {}", wait.label());
+    }
+    for step in &breakdown.steps {
+        let yaku = match step.source {
+            ScoreSource::Riichi => "立直",
+            ScoreSource::Ippatsu => "一发",
+            ScoreSource::Timing(yaku) => yaku.label(),
+            ScoreSource::Yakuhai(role) => role.label(),
+            ScoreSource::BonusSet(TileSuit::Flower) => "四花齐",
+            ScoreSource::BonusSet(_) => "四季齐",
+            _ => continue,
+        };
+        heading = format!("{heading} · {yaku}");
+    }
+    heading
+}
+
+/// Tiles gaining or losing `TileSelected` call for a new preview.
+/// Played and discarded tiles lose it too, so board changes are covered.
+fn mark_preview_dirty(
+    changed_q: Query<(), Changed<TileSelected>>,
+    mut removed: RemovedComponents<TileSelected>,
+    selected_q: Query<Entity, With<TileSelected>>,
+    mut preview: ResMut<SelectionPreview>,
+) {
+    if !changed_q.is_empty() || removed.read().count() > 0 {
+        preview.selected = Some(selected_q.iter().collect());
+    }
+}
+
+/// Score the board as if the selected hand tiles were played next
+fn preview_selection(
+    mut preview: ResMut<SelectionPreview>,
+    hand: Res<PlayerHand>,
+    board: Res<PlayBoard>,
+    tile_q: Query<(&Tile, Option<&TileEnhancement>, Option<&PlayedOn>)>,
+    levels: Res<PatternLevels>,
+    jokers: Res<Jokers>,
+    game_state: Res<GameState>,
+) {
+    let Some(selection) = preview.selected.take() else {
+        return;
+    };
+
+    let scored = |entity: &Entity| {
+        tile_q.get(*entity).ok().map(|(t, enhancement, played)| ScoredTile {
+            id: t.id,
+            enhancement: enhancement.copied(),
+            play: played.map_or(0, |p| p.0),
+        })
+    };
+    let board_tiles: Vec<ScoredTile> = board.tiles.iter().filter_map(scored).collect();
+    let play = game_state.plays_made + 1;
+    let selected: Vec<ScoredTile> = hand
+        .tiles
+        .iter()
+        .filter(|e| selection.contains(e))
+        .filter_map(scored)
+        .map(|tile| ScoredTile { play, ..tile })
+        .collect();
+    if selected.is_empty() {
+        preview.preview = None;
+        return;
+    }
+
+    let mut declared: Vec<Meld> = Vec::new();
+    let mut rejected = None;
+    let mut counted = board.counted_tiles() + selected.len();
+    if game_state.play_mode == PlayMode::Declared {
+        declared = board.melds.iter().map(|d| d.meld).collect();
+        let ids: Vec<TileId> = selected.iter().map(|t| t.id).collect();
+        match check_declared_play(&ids, &board) {
+            Ok(meld) => {
+                if meld.kind == MeldKind::Kong {
+                    counted -= 1;
+                }
+                declared.push(meld);
+            }
+            Err(reason) => rejected = Some(reason),
+        }
+    }
+
+    let context = HandContext {
+        declared,
+        riichi: game_state.riichi,
+        timing: None,
+        winds: Some(Winds {
+            round: game_state.round_wind(),
+            seat: game_state.seat_wind(),
+        }),
+        ruleset: game_state.ruleset,
+        void_suit: game_state.void_suit,
+        bonus: board
+            .bonus
+            .iter()
+            .filter_map(|&e| tile_q.get(e).ok().map(|(t, ..)| t.id))
+            .collect(),
+        debuffed_suit: game_state.debuffed_suit(),
+    };
+    let modifiers = jokers.modifiers();
+    let tiles: Vec<ScoredTile> = board_tiles.iter().chain(&selected).copied().collect();
+    let breakdown = score_hand(&tiles, &context, &levels, &modifiers);
+
+    let before: Vec<TileId> = board_tiles.iter().map(|t| t.id).collect();
+    let after: Vec<TileId> = tiles.iter().map(|t| t.id).collect();
+    preview.preview = Some(PlayPreview {
+        heading: pattern_heading(&breakdown),
+        chips: breakdown.chips,
+        mult: breakdown.mult,
+        total: breakdown.total(),
+        shanten: (shanten(&before), shanten(&after)),
+        over_limit: counted > 14,
+        rejected,
+    });
+}
+
+fn clear_preview(mut preview: ResMut<SelectionPreview>) {
+    *preview = SelectionPreview::default();
+}
+
+fn calculate_score(
+    mut commands: Commands,
+    mut board: ResMut<PlayBoard>,
+    mut tile_q: Query<(&Tile, Option<&TileEnhancement>, Option<&PlayedOn>, &mut Sprite)>,
+    levels: Res<PatternLevels>,
+    jokers: Res<Jokers>,
+    wall: Res<TileWall>,
+    mut game_state: ResMut<GameState>,
+) {
+    // Regroup a free board into its winning melds first,
+    // so tiles score left to right in the order they're shown
+    if board.melds.is_empty() {
+        let ids: Vec<TileId> = board
+            .tiles
+            .iter()
+            .filter_map(|&e| tile_q.get(e).ok().map(|(t, ..)| t.id))
+            .collect();
+        if let Some(melds) = decompose_hand(&ids) {
+            let groups = assign_meld_groups(&ids, &melds);
+            board.melds = melds
+                .into_iter()
+                .enumerate()
+                .map(|(m, meld)| DeclaredMeld {
+                    meld,
+                    tiles: board
+                        .tiles
+                        .iter()
+                        .zip(&groups)
+                        .filter(|(_, g)| **g == Some(m))
+                        .map(|(&e, _)| e)
+                        .collect(),
+                })
+                .collect();
+            board.tiles = board.melds.iter().flat_map(|d| d.tiles.clone()).collect();
+        }
+    }
+
+    // Gather scored tiles from board, left to right
+    let (entities, tiles): (Vec<Entity>, Vec<ScoredTile>) = board
+        .tiles
+        .iter()
+        .filter_map(|&e| tile_q.get(e).ok().map(|t| (e, t)))
+        .map(|(e, (t, enhancement, played, _))| {
+            (
+                e,
+                ScoredTile {
+                    id: t.id,
+                    enhancement: enhancement.copied(),
+                    play: played.map_or(0, |p| p.0),
+                },
+            )
+        })
+        .unzip();
+
+    let context = HandContext {
+        declared: match game_state.play_mode {
+            PlayMode::Declared => board.melds.iter().map(|d| d.meld).collect(),
+            PlayMode::Free => Vec::new(),
+        },
+        riichi: game_state.riichi,
+        timing: Some(RoundTiming {
+            plays_made: game_state.plays_made,
+            plays_remaining: game_state.plays_remaining,
+            discards_made: game_state.discards_made,
+            last_wall_tile: entities.iter().position(|&e| Some(e) == wall.last_tile),
+        }),
+        winds: Some(Winds {
+            round: game_state.round_wind(),
+            seat: game_state.seat_wind(),
+        }),
+        ruleset: game_state.ruleset,
+        void_suit: game_state.void_suit,
+        bonus: board
+            .bonus
+            .iter()
+            .filter_map(|&e| tile_q.get(e).ok().map(|(t, ..)| t.id))
+            .collect(),
+        debuffed_suit: game_state.debuffed_suit(),
+    };
+
+    let modifiers = jokers.modifiers();
+    let breakdown = score_hand(&tiles, &context, &levels, &modifiers);
+
+    // Chips and mult count up from zero as the steps play back
+    game_state.base_ante = Score::ZERO;
+    game_state.multiplier = Score::ZERO;
+
+    let winning = breakdown.winning.and_then(|(index, _)| {
+        let entity = entities[index];
+        let (_, _, _, mut sprite) = tile_q.get_mut(entity).ok()?;
+        let face = sprite.color;
+        sprite.color = WINNING_TILE_TINT;
+        Some((entity, face))
+    });
+
+    let groups = entities
+        .iter()
+        .map(|e| board.melds.iter().position(|d| d.tiles.contains(e)))
+        .collect();
+
+    commands.insert_resource(ScoreAnimation {
+        breakdown,
+        tiles: entities,
+        groups,
+        winning,
+        ..default()
+    });
+}
+
+/// Head the score panel with the hand, and label each meld on the board
+/// with its kind and the yaku it counts toward
+fn show_scored_hand(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    board: Res<PlayBoard>,
+    anim: Res<ScoreAnimation>,
+    game_state: Res<GameState>,
+    mut pattern_text_q: Query<&mut Text, With<HandPatternText>>,
+) {
+    if let Ok(mut text) = pattern_text_q.single_mut() {
+        text.0 = pattern_heading(&anim.breakdown);
+    }
+
+    let font = asset_server.load("fonts/pixel.ttf");
+    let hand = &anim.breakdown.hand;
+    let pattern = hand.pattern;
+    let melds: Vec<Meld> = board.melds.iter().map(|d| d.meld).collect();
+    let winds = Winds {
+        round: game_state.round_wind(),
+        seat: game_state.seat_wind(),
+    };
+    let y = BOARD_Y - TILE_HEIGHT / 2.0 - MELD_LABEL_OFFSET;
+
+    for (meld, x) in melds.iter().zip(meld_centers(&board.melds)) {
+        let mut label = meld.kind.label().to_string();
+        if meld_contributes(pattern, meld, &melds) {
+            label = format!("{label}\n{}", pattern.label());
+        }
+        if hand.winning && !winds.roles(meld).is_empty() {
+            label = format!("{label}\n役牌");
+        }
+        commands.spawn((
+            MeldLabel,
+            Text2d::new(label),
+            TextFont {
+                font: font.clone(),
+                font_size: 12.0,
+                ..default()
+            },
+            TextColor(MELD_LABEL_COLOR),
+            TextLayout::new_with_justify(Justify::Center),
+            Anchor::TOP_CENTER,
+            Transform::from_translation(Vec3::new(x, y, 2.0)),
+        ));
+    }
+}
+
+fn despawn_meld_labels(mut commands: Commands, labels: Query<Entity, With<MeldLabel>>) {
+    for entity in &labels {
+        commands.entity(entity).despawn();
+    }
+}
+
+/// Scale up the tile being scored and, more subtly, the rest of its meld
+fn highlight_scoring_tile(
+    anim: &ScoreAnimation,
+    current: Option<usize>,
+    transform_q: &mut Query<&mut Transform, With<Tile>>,
+) {
+    let current_group = current.and_then(|i| anim.groups.get(i).copied().flatten());
+    for (i, &entity) in anim.tiles.iter().enumerate() {
+        let Ok(mut transform) = transform_q.get_mut(entity) else {
+            continue;
+        };
+        let scale = if Some(i) == current {
+            SCORING_TILE_SCALE
+        } else if current_group.is_some() && anim.groups[i] == current_group {
+            SCORING_MELD_SCALE
+        } else {
+            1.0
+        };
+        transform.scale = Vec3::splat(scale);
+    }
+}
+
+/// Play the score breakdown back one step at a time, then slam in the total
+fn animate_score(
+    mut commands: Commands,
+    time: Res<Time>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    speed: Res<ScoreAnimationSpeed>,
+    mut anim: ResMut<ScoreAnimation>,
+    mut game_state: ResMut<GameState>,
+    mut transform_q: Query<&mut Transform, With<Tile>>,
+) {
+    // Click to skip straight to the total
+    if mouse.just_pressed(MouseButton::Left)
+        && matches!(anim.stage, AnimationStage::Steps | AnimationStage::Slam)
+    {
+        anim.next_step = anim.breakdown.steps.len();
+        anim.stage = AnimationStage::Slam;
+        anim.timer = SLAM_DELAY_SECONDS;
+        highlight_scoring_tile(&anim, None, &mut transform_q);
+    } else {
+        anim.timer += time.delta_secs() * speed.multiplier();
+    }
+
+    loop {
+        match anim.stage {
+            AnimationStage::Steps => {
+                if anim.timer < STEP_SECONDS {
+                    return;
+                }
+                anim.timer -= STEP_SECONDS;
+                let Some(step) = anim.breakdown.steps.get(anim.next_step) else {
+                    highlight_scoring_tile(&anim, None, &mut transform_q);
+                    anim.stage = AnimationStage::Slam;
+                    continue;
+                };
+                game_state.base_ante = step.chips;
+                game_state.multiplier = step.mult;
+                let effect = step.effect;
+                let current = match step.source {
+                    ScoreSource::Tile { index, .. } => Some(index),
+                    ScoreSource::Wait(_) => anim.breakdown.winning.map(|(index, _)| index),
+                    _ => None,
+                };
+                commands.trigger(ScoreStepEvent {
+                    effect,
+                    tile: current.and_then(|index| anim.tiles.get(index).copied()),
+                });
+                anim.next_step += 1;
+                highlight_scoring_tile(&anim, current, &mut transform_q);
+            }
+            AnimationStage::Slam => {
+                if anim.timer < SLAM_DELAY_SECONDS {
+                    return;
+                }
+                anim.timer = 0.0;
+                let breakdown = &anim.breakdown;
+                let total = breakdown.total();
+                game_state.base_ante = breakdown.chips;
+                game_state.multiplier = breakdown.mult;
+                game_state.current_score += total;
+                commands.trigger(ScoreCalculatedEvent {
+                    base: breakdown.chips,
+                    multiplier: breakdown.mult,
+                    total,
+                    pattern_name: pattern_heading(breakdown),
+                    hand: breakdown.hand.clone(),
+                });
+                anim.stage = AnimationStage::Hold;
+            }
+            AnimationStage::Hold => {
+                if anim.timer < HOLD_SECONDS {
+                    return;
+                }
+                anim.stage = AnimationStage::Done;
+                commands.trigger(RoundEndedEvent {
+                    passed: game_state.current_score >= game_state.target_score,
+                });
+            }
+            AnimationStage::Done => return,
+        }
+    }
+}
+
+fn reset_tile_highlight(
+    anim: Res<ScoreAnimation>,
+    mut transform_q: Query<&mut Transform, With<Tile>>,
+    mut sprite_q: Query<&mut Sprite, With<Tile>>,
+) {
+    highlight_scoring_tile(&anim, None, &mut transform_q);
+    if let Some((entity, face)) = anim.winning {
+        if let Ok(mut sprite) = sprite_q.get_mut(entity) {
+            sprite.color = face;
+        }
+    }
+}
+
+/// Observer: once the score is in, move on to the round result
+fn on_round_ended(_trigger: On<RoundEndedEvent>, mut next_phase: ResMut<NextState<PlayPhase>>) {
+    next_phase.set(PlayPhase::RoundResult);
+}
+
+fn evaluate_round_result(
+    mut game_state: ResMut<GameState>,
+    mut profile: ResMut<Profile>,
+    mut next_app_state: ResMut<NextState<AppState>>,
+    mut next_phase: ResMut<NextState<PlayPhase>>,
+    mut commands: Commands,
+    mut board: ResMut<PlayBoard>,
+) {
+    let passed = game_state.current_score >= game_state.target_score;
+
+    if passed {
+        let reward = game_state.blind_reward();
+        game_state.money += reward;
+
+        // Advance to next sub-round, or win the run after the final boss
+        if !game_state.advance_sub_round() {
+            profile.record_win(game_state.stake);
+            profile.save();
+            next_app_state.set(AppState::Victory);
+            return;
+        }
+        game_state.reset_for_sub_round();
+
+        // Hide board and bonus tiles
+        for &entity in board.tiles.iter().chain(&board.bonus) {
+            commands.entity(entity).insert(Visibility::Hidden);
+        }
+        board.clear();
+
+        // Visit the shop before the next sub-round
+        next_phase.set(PlayPhase::Shop);
+    } else {
+        // Failed: go to game over
+        next_app_state.set(AppState::GameOver);
+    }
+}
+
+// ============ Unit Tests ============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_tiles(specs: &[(TileSuit, u8, u8)]) -> Vec<TileId> {
+        let mut tiles = Vec::new();
+        for &(suit, value, count) in specs {
+            for _ in 0..count {
+                tiles.push(TileId { suit, value });
+            }
+        }
+        tiles
+    }
+
+    #[test]
+    fn test_standard_win_simple() {
+        // 1m 1m 1m 2m 3m 4m 5m 6m 7m 8m 9m 9m 9m 3p 3p = nope (15 tiles)
+        // Let's do a proper 14-tile hand: 1m×3, 2m,3m,4m, 5m,6m,7m, 8m,9m,9m×3 = 14 nope
+        // Simple: 1m×2 (pair) + 2m,3m,4m + 5m,6m,7m + 1p,2p,3p + 4p,5p,6p = 14
+        let tiles = make_tiles(&[
+            (TileSuit::Man, 1, 2),
+            (TileSuit::Man, 2, 1), (TileSuit::Man, 3, 1), (TileSuit::Man, 4, 1),
+            (TileSuit::Man, 5, 1), (TileSuit::Man, 6, 1), (TileSuit::Man, 7, 1),
+            (TileSuit::Pin, 1, 1), (TileSuit::Pin, 2, 1), (TileSuit::Pin, 3, 1),
+            (TileSuit::Pin, 4, 1), (TileSuit::Pin, 5, 1), (TileSuit::Pin, 6, 1),
+        ]);
+        assert_eq!(tiles.len(), 14);
+        let result = evaluate_hand(&tiles, &PatternLevels::default());
+        assert!(result.multiplier >= 1, "Should be a winning hand: {:?}", result);
+    }
+
+    #[test]
+    fn test_seven_pairs() {
+        let tiles = make_tiles(&[
+            (TileSuit::Man, 1, 2),
+            (TileSuit::Man, 3, 2),
+            (TileSuit::Man, 5, 2),
+            (TileSuit::Man, 7, 2),
+            (TileSuit::Pin, 2, 2),
+            (TileSuit::Pin, 4, 2),
+            (TileSuit::Sou, 6, 2),
+        ]);
+        assert_eq!(tiles.len(), 14);
+        let result = evaluate_hand(&tiles, &PatternLevels::default());
+        assert_eq!(result.pattern_name, "七对子");
+        assert_eq!(result.multiplier, 4);
+    }
+
+    #[test]
+    fn test_thirteen_orphans() {
+        let tiles = make_tiles(&[
+            (TileSuit::Man, 1, 2), // pair
+            (TileSuit::Man, 9, 1),
+            (TileSuit::Pin, 1, 1),
+            (TileSuit::Pin, 9, 1),
+            (TileSuit::Sou, 1, 1),
+            (TileSuit::Sou, 9, 1),
+            (TileSuit::Wind, 1, 1),
+            (TileSuit::Wind, 2, 1),
+            (TileSuit::Wind, 3, 1),
+            (TileSuit::Wind, 4, 1),
+            (TileSuit::Dragon, 1, 1),
+            (TileSuit::Dragon, 2, 1),
+            (TileSuit::Dragon, 3, 1),
+        ]);
+        assert_eq!(tiles.len(), 14);
+        let result = evaluate_hand(&tiles, &PatternLevels::default());
+        assert_eq!(result.pattern_name, "国士无双");
+        assert_eq!(result.multiplier, 13);
+        assert!(result.pattern.is_limit());
+        assert!(!HandPattern::FullFlush.is_limit());
+    }
+
+    #[test]
+    fn test_chinitsu() {
+        // All man tiles: 1m×3, 2m,3m,4m, 5m,6m,7m, 8m,9m,9m×3 => nah, let me count
+        // 1m×2 (pair) + 1m,2m,3m + 4m,5m,6m + 7m,8m,9m + 7m,8m,9m = 14
+        // 1m×3, 2m×1, 3m×1, 4m×1, 5m×1, 6m×1, 7m×2, 8m×2, 9m×2 = 14
+        let tiles = make_tiles(&[
+            (TileSuit::Man, 1, 3),
+            (TileSuit::Man, 2, 1),
             (TileSuit::Man, 3, 1),
             (TileSuit::Man, 4, 1),
             (TileSuit::Man, 5, 1),
@@ -495,7 +1877,7 @@ mod tests {
             (TileSuit::Man, 9, 2),
         ]);
         assert_eq!(tiles.len(), 14);
-        let result = evaluate_hand(&tiles);
+        let result = evaluate_hand(&tiles, &PatternLevels::default());
         assert_eq!(result.pattern_name, "清一色");
         assert_eq!(result.multiplier, 8);
     }
@@ -511,7 +1893,7 @@ mod tests {
             (TileSuit::Wind, 1, 2),
         ]);
         assert_eq!(tiles.len(), 14);
-        let result = evaluate_hand(&tiles);
+        let result = evaluate_hand(&tiles, &PatternLevels::default());
         assert_eq!(result.pattern_name, "对对和");
         assert_eq!(result.multiplier, 4);
     }
@@ -536,7 +1918,7 @@ mod tests {
             (TileSuit::Man, 9, 1),
         ]);
         assert_eq!(tiles.len(), 14);
-        let result = evaluate_hand(&tiles);
+        let result = evaluate_hand(&tiles, &PatternLevels::default());
         assert_eq!(result.pattern_name, "未和牌");
     }
 
@@ -552,8 +1934,548 @@ mod tests {
             (TileSuit::Pin, 6, 1), (TileSuit::Pin, 7, 1), (TileSuit::Pin, 8, 1), // seq 6,7,8
         ]);
         assert_eq!(tiles.len(), 14);
-        let result = evaluate_hand(&tiles);
+        let result = evaluate_hand(&tiles, &PatternLevels::default());
         assert_eq!(result.pattern_name, "断幺九");
         assert_eq!(result.multiplier, 2);
     }
+
+    #[test]
+    fn test_pattern_levels() {
+        let tiles = make_tiles(&[
+            (TileSuit::Man, 1, 2),
+            (TileSuit::Man, 3, 2),
+            (TileSuit::Man, 5, 2),
+            (TileSuit::Man, 7, 2),
+            (TileSuit::Pin, 2, 2),
+            (TileSuit::Pin, 4, 2),
+            (TileSuit::Sou, 6, 2),
+        ]);
+        let mut levels = PatternLevels::default();
+        levels.level_up(HandPattern::SevenPairs);
+        levels.level_up(HandPattern::SevenPairs);
+
+        let result = evaluate_hand(&tiles, &levels);
+        assert_eq!(result.pattern, HandPattern::SevenPairs);
+        assert_eq!(result.level, 3);
+        assert_eq!(result.base, 30);
+        assert_eq!(result.multiplier, 6);
+
+        // Levels of other patterns don't leak
+        assert_eq!(levels.level(HandPattern::AllTriplets), 1);
+    }
+
+    #[test]
+    fn test_tile_chips() {
+        // 1m×3, 2m..6m, 7m×2, 8m×2, 9m×2 (清一色)
+        let tiles = make_tiles(&[
+            (TileSuit::Man, 1, 3),
+            (TileSuit::Man, 2, 1),
+            (TileSuit::Man, 3, 1),
+            (TileSuit::Man, 4, 1),
+            (TileSuit::Man, 5, 1),
+            (TileSuit::Man, 6, 1),
+            (TileSuit::Man, 7, 2),
+            (TileSuit::Man, 8, 2),
+            (TileSuit::Man, 9, 2),
+        ]);
+        assert_eq!(TileId { suit: TileSuit::Man, value: 5 }.chips(), 5);
+        assert_eq!(TileId { suit: TileSuit::Man, value: 9 }.chips(), 10);
+        assert_eq!(TileId { suit: TileSuit::Dragon, value: 1 }.chips(), 10);
+
+        // Terminals: 1m×3 + 9m×2 = 50, simples: 2+3+4+5+6 + 7×2 + 8×2 = 50
+        let breakdown = score_hand(&plain(&tiles), &HandContext::default(), &PatternLevels::default(), &[]);
+        assert_eq!(breakdown.chips, 10 + 100);
+        assert_eq!(breakdown.mult, 8);
+    }
+
+    fn plain(tiles: &[TileId]) -> Vec<ScoredTile> {
+        tiles
+            .iter()
+            .map(|&id| ScoredTile {
+                id,
+                enhancement: None,
+                play: 1,
+            })
+            .collect()
+    }
+
+    fn seven_pairs() -> Vec<TileId> {
+        make_tiles(&[
+            (TileSuit::Man, 2, 2),
+            (TileSuit::Man, 4, 2),
+            (TileSuit::Man, 6, 2),
+            (TileSuit::Man, 8, 2),
+            (TileSuit::Pin, 2, 2),
+            (TileSuit::Pin, 4, 2),
+            (TileSuit::Sou, 6, 2),
+        ])
+    }
+
+    #[test]
+    fn test_pipeline_effect_order() {
+        // 七对子: 10 chips ×4, 单骑 wait +20, tiles add 2+2+4+4+6+6+8+8+2+2+4+4+6+6 = 64 chips
+        let mut tiles = plain(&seven_pairs());
+        let base = score_hand(&tiles, &HandContext::default(), &PatternLevels::default(), &[]);
+        assert_eq!(base.chips, 94);
+        assert_eq!(base.mult, 4);
+
+        // +4 then ×2 = 16, ×2 then +4 = 12
+        tiles[0].enhancement = Some(TileEnhancement::Mult);
+        tiles[1].enhancement = Some(TileEnhancement::Glass);
+        let add_first = score_hand(&tiles, &HandContext::default(), &PatternLevels::default(), &[]);
+        assert_eq!(add_first.mult, 16);
+        // Steps carry the running totals, so the trace ends at the hand's score
+        assert_eq!(add_first.steps[0].source, ScoreSource::Pattern);
+        let last = add_first.steps.last().unwrap();
+        assert_eq!((last.chips, last.mult), (add_first.chips, add_first.mult));
+
+        tiles.swap(0, 1);
+        let mul_first = score_hand(&tiles, &HandContext::default(), &PatternLevels::default(), &[]);
+        assert_eq!(mul_first.mult, 12);
+    }
+
+    struct Echoing;
+
+    impl ScoreModifier for Echoing {
+        fn name(&self) -> &str {
+            "echoing"
+        }
+
+        fn retriggers(&self, tile: &ScoredTile) -> u32 {
+            u32::from(tile.id.suit == TileSuit::Sou)
+        }
+
+        fn on_hand_scored(&self, _pattern: HandPattern) -> Vec<ScoreEffect> {
+            vec![ScoreEffect::XMult(3u32.into())]
+        }
+    }
+
+    #[test]
+    fn test_pipeline_retriggers_and_modifiers() {
+        let mut tiles = plain(&seven_pairs());
+        tiles[0].enhancement = Some(TileEnhancement::Echo);
+
+        // 2m retriggers once from its enhancement, both 6s once from the modifier
+        let breakdown = score_hand(&tiles, &HandContext::default(), &PatternLevels::default(), &[&Echoing]);
+        assert_eq!(breakdown.chips, 94 + 2 + 6 + 6);
+        assert_eq!(breakdown.mult, 12);
+
+        let retriggered: Vec<usize> = breakdown
+            .steps
+            .iter()
+            .filter_map(|step| match step.source {
+                ScoreSource::Tile { index, trigger: 1 } => Some(index),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(retriggered, vec![0, 12, 13]);
+        assert_eq!(
+            breakdown.steps.last().map(|s| &s.source),
+            Some(&ScoreSource::Modifier("echoing".into()))
+        );
+
+        // Non-winning hands never trigger tiles
+        let no_win = score_hand(&tiles[..5], &HandContext::default(), &PatternLevels::default(), &[&Echoing]);
+        assert_eq!(no_win.chips, 5);
+        assert_eq!(no_win.mult, 1);
+    }
+
+    #[test]
+    fn test_jokers_and_debuffed_suit() {
+        let tile = |suit, value| ScoredTile {
+            id: TileId { suit, value },
+            enhancement: None,
+            play: 1,
+        };
+        assert_eq!(JokerKind::Dragon.on_tile_scored(&tile(TileSuit::Dragon, 1)).len(), 1);
+        assert!(JokerKind::Dragon.on_tile_scored(&tile(TileSuit::Man, 1)).is_empty());
+        assert_eq!(JokerKind::Terminal.on_tile_scored(&tile(TileSuit::Pin, 9)).len(), 1);
+        assert_eq!(JokerKind::Fives.retriggers(&tile(TileSuit::Sou, 5)), 1);
+        assert_eq!(JokerKind::Fives.retriggers(&tile(TileSuit::Wind, 5)), 0);
+        assert!(JokerKind::Flush.on_hand_scored(HandPattern::SevenPairs).is_empty());
+        assert_eq!(
+            JokerKind::Flush.on_hand_scored(HandPattern::FullFlush),
+            vec![ScoreEffect::XMult(3u32.into())]
+        );
+
+        // Owned jokers join the pipeline in slot order
+        let jokers = Jokers {
+            slots: vec![
+                Joker { kind: JokerKind::Fives, eternal: false },
+                Joker { kind: JokerKind::Terminal, eternal: true },
+            ],
+        };
+        let names: Vec<&str> = jokers.modifiers().iter().map(|m| m.name()).collect();
+        assert_eq!(names, vec!["五福小丑", "幺九小丑"]);
+
+        // A boss's debuffed suit drops the 2p 2p 4p 4p from scoring
+        let tiles = plain(&seven_pairs());
+        let context = HandContext {
+            debuffed_suit: Some(TileSuit::Pin),
+            ..default()
+        };
+        let breakdown = score_hand(&tiles, &context, &PatternLevels::default(), &[]);
+        assert_eq!(breakdown.chips, 94 - 12);
+        assert!(breakdown.steps.iter().all(|step| match step.source {
+            ScoreSource::Tile { index, .. } => tiles[index].id.suit != TileSuit::Pin,
+            _ => true,
+        }));
+    }
+
+    #[test]
+    fn test_decompose_hand() {
+        let tiles = make_tiles(&[
+            (TileSuit::Man, 1, 3),
+            (TileSuit::Man, 5, 3),
+            (TileSuit::Pin, 9, 3),
+            (TileSuit::Dragon, 1, 3),
+            (TileSuit::Wind, 1, 2),
+        ]);
+        let melds = decompose_hand(&tiles).expect("toitoi decomposes");
+        assert_eq!(melds.len(), 5);
+        assert_eq!(melds[0].kind, MeldKind::Pair);
+        assert_eq!(melds[0].first, TileId { suit: TileSuit::Wind, value: 1 });
+        assert!(melds[1..].iter().all(|m| m.kind == MeldKind::Triplet));
+
+        let groups = assign_meld_groups(&tiles, &melds);
+        assert!(groups.iter().all(|g| g.is_some()));
+        assert_eq!(groups[0], Some(1)); // 1m triplet
+
+        let pairs = decompose_hand(&seven_pairs()).expect("seven pairs decomposes");
+        assert_eq!(pairs.len(), 7);
+        assert!(pairs.iter().all(|m| m.kind == MeldKind::Pair));
+
+        assert_eq!(decompose_hand(&seven_pairs()[..5]), None);
+    }
+
+    #[test]
+    fn test_winning_tile_wait() {
+        let ids = make_tiles(&[
+            (TileSuit::Man, 1, 1),
+            (TileSuit::Man, 2, 1),
+            (TileSuit::Man, 3, 1),
+            (TileSuit::Man, 5, 2),
+            (TileSuit::Pin, 4, 1),
+            (TileSuit::Pin, 5, 1),
+            (TileSuit::Pin, 6, 1),
+            (TileSuit::Sou, 2, 1),
+            (TileSuit::Sou, 3, 1),
+            (TileSuit::Sou, 4, 1),
+            (TileSuit::Wind, 1, 3),
+        ]);
+        let won_on = |last: &[TileId]| {
+            let mut tiles = plain(&ids);
+            for id in last {
+                let i = tiles.iter().position(|t| t.id == *id && t.play == 1).unwrap();
+                tiles[i].play = 2;
+            }
+            let melds = decompose_hand(&ids).unwrap();
+            find_winning_tile(&tiles, &melds).map(|(i, wait)| (tiles[i].id, wait))
+        };
+        let man = |value| TileId { suit: TileSuit::Man, value };
+        let sou = |value| TileId { suit: TileSuit::Sou, value };
+        let east = TileId { suit: TileSuit::Wind, value: 1 };
+
+        assert_eq!(won_on(&[man(3)]), Some((man(3), WaitShape::Penchan)));
+        assert_eq!(won_on(&[man(2)]), Some((man(2), WaitShape::Kanchan)));
+        assert_eq!(won_on(&[man(5)]), Some((man(5), WaitShape::Tanki)));
+        assert_eq!(won_on(&[sou(4)]), Some((sou(4), WaitShape::Ryanmen)));
+        assert_eq!(won_on(&[east]), Some((east, WaitShape::Shanpon)));
+
+        // A multi-tile last play reads as its easiest wait
+        assert_eq!(won_on(&[man(3), sou(4)]), Some((sou(4), WaitShape::Ryanmen)));
+
+        // Hard waits add chips after the pattern
+        let mut tiles = plain(&ids);
+        tiles[2].play = 2;
+        let breakdown = score_hand(&tiles, &HandContext::default(), &PatternLevels::default(), &[]);
+        assert_eq!(breakdown.winning, Some((2, WaitShape::Penchan)));
+        assert_eq!(breakdown.steps[2].source, ScoreSource::Wait(WaitShape::Penchan));
+    }
+
+    #[test]
+    fn test_declared_melds() {
+        let man = |value| TileId { suit: TileSuit::Man, value };
+        let east = TileId { suit: TileSuit::Wind, value: 1 };
+        let meld = |kind, first| Some(Meld { kind, first });
+
+        assert_eq!(declare_meld(&[man(4), man(2), man(3)]), meld(MeldKind::Sequence, man(2)));
+        assert_eq!(declare_meld(&[east; 2]), meld(MeldKind::Pair, east));
+        assert_eq!(declare_meld(&[east; 3]), meld(MeldKind::Triplet, east));
+        assert_eq!(declare_meld(&[east; 4]), meld(MeldKind::Kong, east));
+        assert_eq!(declare_meld(&[man(1), man(2), man(4)]), None);
+        assert_eq!(declare_meld(&[man(1)]), None);
+        let winds: Vec<TileId> = (1..=3).map(|value| TileId { suit: TileSuit::Wind, value }).collect();
+        assert_eq!(declare_meld(&winds), None);
+
+        // 15 tiles with a kong still win, and clean play adds +1 mult per meld
+        let ids = make_tiles(&[
+            (TileSuit::Man, 1, 1),
+            (TileSuit::Man, 2, 1),
+            (TileSuit::Man, 3, 1),
+            (TileSuit::Man, 5, 2),
+            (TileSuit::Pin, 4, 1),
+            (TileSuit::Pin, 5, 1),
+            (TileSuit::Pin, 6, 1),
+            (TileSuit::Sou, 2, 1),
+            (TileSuit::Sou, 3, 1),
+            (TileSuit::Sou, 4, 1),
+            (TileSuit::Wind, 1, 4),
+        ]);
+        let declared: Vec<Meld> = [
+            &ids[0..3],
+            &ids[3..5],
+            &ids[5..8],
+            &ids[8..11],
+            &ids[11..15],
+        ]
+        .iter()
+        .map(|tiles| declare_meld(tiles).unwrap())
+        .collect();
+        let free = score_hand(&plain(&ids), &HandContext::default(), &PatternLevels::default(), &[]);
+        assert_eq!(free.hand.pattern, HandPattern::NoWin);
+
+        let context = HandContext {
+            declared,
+            ..default()
+        };
+        let clean = score_hand(&plain(&ids), &context, &PatternLevels::default(), &[]);
+        assert_eq!(clean.hand.pattern, HandPattern::Standard);
+        assert_eq!(clean.mult, 1 + 5);
+        let declared_effects: Vec<ScoreEffect> = clean
+            .steps
+            .iter()
+            .filter(|step| step.source == ScoreSource::Declared)
+            .map(|step| step.effect)
+            .collect();
+        assert_eq!(
+            declared_effects,
+            vec![ScoreEffect::Chips(30u32.into()), ScoreEffect::AddMult(5u32.into())]
+        );
+    }
+
+    #[test]
+    fn test_meld_contributes() {
+        let tiles = make_tiles(&[
+            (TileSuit::Man, 1, 1), (TileSuit::Man, 2, 1), (TileSuit::Man, 3, 1),
+            (TileSuit::Man, 4, 1), (TileSuit::Man, 5, 1), (TileSuit::Man, 6, 1),
+            (TileSuit::Man, 7, 1), (TileSuit::Man, 8, 1), (TileSuit::Man, 9, 1),
+            (TileSuit::Pin, 2, 2),
+            (TileSuit::Wind, 1, 3),
+        ]);
+        assert_eq!(detect_pattern(&tiles), HandPattern::PureStraight);
+        let melds = decompose_hand(&tiles).unwrap();
+        let contributing: Vec<MeldKind> = melds
+            .iter()
+            .filter(|m| meld_contributes(HandPattern::PureStraight, m, &melds))
+            .map(|m| m.kind)
+            .collect();
+        assert_eq!(contributing, vec![MeldKind::Sequence; 3]);
+
+        let triplets = melds
+            .iter()
+            .filter(|m| meld_contributes(HandPattern::AllTriplets, m, &melds))
+            .count();
+        assert_eq!(triplets, 1);
+    }
+
+    #[test]
+    fn test_riichi() {
+        let ids = make_tiles(&[
+            (TileSuit::Man, 1, 1),
+            (TileSuit::Man, 2, 1),
+            (TileSuit::Man, 3, 1),
+            (TileSuit::Man, 5, 2),
+            (TileSuit::Pin, 4, 1),
+            (TileSuit::Pin, 5, 1),
+            (TileSuit::Pin, 6, 1),
+            (TileSuit::Sou, 2, 1),
+            (TileSuit::Sou, 3, 1),
+            (TileSuit::Sou, 4, 1),
+            (TileSuit::Wind, 1, 3),
+        ]);
+        // Board holds 9 tiles, the hand can supply 4 more that wait on 1 tile
+        let junk = make_tiles(&[(TileSuit::Dragon, 1, 1), (TileSuit::Pin, 9, 1), (TileSuit::Sou, 7, 1)]);
+        let hand: Vec<TileId> = ids[9..13].iter().chain(&junk).copied().collect();
+        let rules = RulesetKind::Riichi.rules();
+        assert!(can_reach_tenpai(&ids[..9], &hand, rules));
+        assert!(!can_reach_tenpai(&ids[..9], &junk, rules));
+        assert!(!can_reach_tenpai(&ids, &hand, rules));
+
+        // Riichi after play 1: winning on play 2 is 一发, on play 3 it isn't
+        let mut tiles = plain(&ids);
+        tiles[13].play = 2;
+        let context = HandContext {
+            riichi: Some(1),
+            ..default()
+        };
+        let ippatsu = score_hand(&tiles, &context, &PatternLevels::default(), &[]);
+        tiles[13].play = 3;
+        let late = score_hand(&tiles, &context, &PatternLevels::default(), &[]);
+        let sources = |b: &ScoreBreakdown| -> Vec<ScoreSource> {
+            b.steps
+                .iter()
+                .map(|s| s.source.clone())
+                .filter(|s| matches!(s, ScoreSource::Riichi | ScoreSource::Ippatsu))
+                .collect()
+        };
+        assert_eq!(sources(&ippatsu), vec![ScoreSource::Riichi, ScoreSource::Ippatsu]);
+        assert_eq!(sources(&late), vec![ScoreSource::Riichi]);
+    }
+
+    #[test]
+    fn test_timing_yaku() {
+        let mut tiles = plain(&seven_pairs());
+        let first_play = RoundTiming {
+            plays_made: 1,
+            plays_remaining: 3,
+            discards_made: 2,
+            last_wall_tile: None,
+        };
+        assert_eq!(first_play.yaku(&tiles), vec![TimingYaku::FirstPlay]);
+
+        // Last play, finished with the wall's final tile and no discards
+        tiles[5].play = 4;
+        let last_play = RoundTiming {
+            plays_made: 4,
+            plays_remaining: 0,
+            discards_made: 0,
+            last_wall_tile: Some(5),
+        };
+        assert_eq!(
+            last_play.yaku(&tiles),
+            vec![TimingYaku::LastPlay, TimingYaku::LastTile, TimingYaku::NoDiscards]
+        );
+
+        // The wall's last tile only counts if it came in the final play
+        tiles[5].play = 1;
+        tiles[6].play = 4;
+        assert!(!last_play.yaku(&tiles).contains(&TimingYaku::LastTile));
+
+        let tiles = plain(&seven_pairs());
+        let context = HandContext {
+            timing: Some(first_play),
+            ..default()
+        };
+        let breakdown = score_hand(&tiles, &context, &PatternLevels::default(), &[]);
+        assert!(breakdown
+            .steps
+            .iter()
+            .any(|step| step.source == ScoreSource::Timing(TimingYaku::FirstPlay)));
+        assert_eq!(pattern_heading(&breakdown), "七对子 Lv.1 · 单骑 · 天和");
+
+        // Losing hands earn no timing yaku
+        let no_win = score_hand(&tiles[..5], &context, &PatternLevels::default(), &[]);
+        assert!(no_win.steps.iter().all(|step| step.source == ScoreSource::Pattern));
+    }
+
+    #[test]
+    fn test_wind_yakuhai() {
+        let tiles = plain(&make_tiles(&[
+            (TileSuit::Man, 1, 1),
+            (TileSuit::Man, 2, 1),
+            (TileSuit::Man, 3, 1),
+            (TileSuit::Man, 5, 2),
+            (TileSuit::Pin, 4, 1),
+            (TileSuit::Pin, 5, 1),
+            (TileSuit::Pin, 6, 1),
+            (TileSuit::Sou, 2, 1),
+            (TileSuit::Sou, 3, 1),
+            (TileSuit::Sou, 4, 1),
+            (TileSuit::Wind, 2, 3),
+        ]));
+        let wind = |value| TileId { suit: TileSuit::Wind, value };
+        let yakuhai = |round, seat| {
+            let context = HandContext {
+                winds: Some(Winds { round, seat }),
+                ..default()
+            };
+            score_hand(&tiles, &context, &PatternLevels::default(), &[])
+                .steps
+                .iter()
+                .filter_map(|step| match step.source {
+                    ScoreSource::Yakuhai(role) => Some(role),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(yakuhai(wind(1), wind(3)), vec![]);
+        assert_eq!(yakuhai(wind(2), wind(1)), vec![WindRole::Round]);
+        assert_eq!(yakuhai(wind(1), wind(2)), vec![WindRole::Seat]);
+        // 连风牌 counts twice
+        assert_eq!(yakuhai(wind(2), wind(2)), vec![WindRole::Round, WindRole::Seat]);
+    }
+
+    #[test]
+    fn test_bonus_tiles() {
+        let flower = |value| TileId { suit: TileSuit::Flower, value };
+        let season = |value| TileId { suit: TileSuit::Season, value };
+        for id in [flower(1), season(4)] {
+            assert_eq!(TileId::from_index(id.to_index()), id);
+        }
+        assert_eq!(build_count_array(&[flower(1), season(2)]), [0; 34]);
+
+        let tiles = plain(&make_tiles(&[
+            (TileSuit::Man, 1, 1),
+            (TileSuit::Man, 2, 1),
+            (TileSuit::Man, 3, 1),
+            (TileSuit::Man, 5, 2),
+            (TileSuit::Pin, 4, 1),
+            (TileSuit::Pin, 5, 1),
+            (TileSuit::Pin, 6, 1),
+            (TileSuit::Sou, 2, 1),
+            (TileSuit::Sou, 3, 1),
+            (TileSuit::Sou, 4, 1),
+            (TileSuit::Wind, 2, 3),
+        ]));
+        let wind = |value| TileId { suit: TileSuit::Wind, value };
+        let context = HandContext {
+            winds: Some(Winds { round: wind(1), seat: wind(3) }),
+            bonus: vec![flower(1), flower(2), flower(3), flower(4), season(3)],
+            ..default()
+        };
+        let breakdown = score_hand(&tiles, &context, &PatternLevels::default(), &[]);
+        let bonus: Vec<&ScoreEffect> = breakdown
+            .steps
+            .iter()
+            .filter(|step| matches!(step.source, ScoreSource::Bonus(_) | ScoreSource::BonusSet(_)))
+            .map(|step| &step.effect)
+            .collect();
+        // 竹 and 秋 match the 西 seat; all four flowers make a set
+        let add = |n: u32| ScoreEffect::AddMult(n.into());
+        assert_eq!(
+            bonus,
+            vec![&add(1), &add(1), &add(3), &add(1), &add(3), &ScoreEffect::XMult(2u32.into())]
+        );
+    }
+
+    #[test]
+    fn test_shanten() {
+        use TileSuit::*;
+        assert_eq!(shanten(&[]), 8);
+
+        // Complete: 11m 234m 567m 123p 456p
+        let win = make_tiles(&[
+            (Man, 1, 2),
+            (Man, 2, 1), (Man, 3, 1), (Man, 4, 1),
+            (Man, 5, 1), (Man, 6, 1), (Man, 7, 1),
+            (Pin, 1, 1), (Pin, 2, 1), (Pin, 3, 1),
+            (Pin, 4, 1), (Pin, 5, 1), (Pin, 6, 1),
+        ]);
+        assert_eq!(shanten(&win), -1);
+        // Dropping one tile leaves it tenpai
+        assert_eq!(shanten(&win[1..]), 0);
+
+        // Six pairs and a single: tenpai for seven pairs
+        let pairs = make_tiles(&[
+            (Man, 1, 2), (Man, 5, 2), (Pin, 3, 2), (Pin, 9, 2),
+            (Sou, 2, 2), (Wind, 1, 2), (Dragon, 3, 1),
+        ]);
+        assert_eq!(shanten(&pairs), 0);
+
+        // One meld and a partial on an otherwise empty board
+        let partial = make_tiles(&[(Man, 1, 1), (Man, 2, 1), (Man, 3, 1), (Sou, 4, 1), (Sou, 5, 1)]);
+        assert_eq!(shanten(&partial), 5);
+    }
 }
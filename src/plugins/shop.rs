@@ -0,0 +1,225 @@
+use bevy::prelude::*;
+use rand::seq::SliceRandom;
+use rand::{thread_rng, Rng};
+
+use crate::events::*;
+use crate::plugins::board::ActionHistory;
+use crate::plugins::game::PlayPhase;
+use crate::plugins::scoring::{HandPattern, JokerKind};
+use crate::resources::*;
+
+/// Number of pattern cards offered after each cleared blind
+const SHOP_CARD_COUNT: usize = 2;
+pub const PATTERN_CARD_PRICE: u32 = 3;
+/// Number of jokers offered after each cleared blind
+const SHOP_JOKER_COUNT: usize = 2;
+pub const JOKER_PRICE: u32 = 5;
+pub const JOKER_SELL_PRICE: u32 = 2;
+/// Chance a joker on offer is eternal, from 黑注 up
+const ETERNAL_CHANCE: f64 = 0.3;
+/// 悔牌 +1: one more undo per sub-round for the rest of the run
+pub const UNDO_PERK_PRICE: u32 = 4;
+
+pub struct ShopPlugin;
+
+impl Plugin for ShopPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ShopOffers>()
+            .add_observer(on_buy_pattern_card)
+            .add_observer(on_buy_joker)
+            .add_observer(on_sell_joker)
+            .add_observer(on_buy_undo_perk)
+            .add_observer(on_leave_shop)
+            .add_systems(OnEnter(PlayPhase::Shop), roll_shop_offers);
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ShopCard {
+    pub pattern: HandPattern,
+    pub sold: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct ShopJoker {
+    pub joker: Joker,
+    pub sold: bool,
+}
+
+/// Pattern cards and jokers on sale in the current shop visit
+#[derive(Resource, Debug, Default)]
+pub struct ShopOffers {
+    pub cards: Vec<ShopCard>,
+    pub jokers: Vec<ShopJoker>,
+    pub undo_perk_sold: bool,
+}
+
+fn roll_shop_offers(mut offers: ResMut<ShopOffers>, game_state: Res<GameState>) {
+    let mut rng = thread_rng();
+    offers.undo_perk_sold = false;
+    offers.cards = HandPattern::UPGRADABLE
+        .choose_multiple(&mut rng, SHOP_CARD_COUNT)
+        .map(|&pattern| ShopCard {
+            pattern,
+            sold: false,
+        })
+        .collect();
+    offers.jokers = JokerKind::ALL
+        .choose_multiple(&mut rng, SHOP_JOKER_COUNT)
+        .map(|&kind| ShopJoker {
+            joker: Joker {
+                kind,
+                eternal: game_state.stake.eternal_jokers() && rng.gen_bool(ETERNAL_CHANCE),
+            },
+            sold: false,
+        })
+        .collect();
+}
+
+/// Observer: buy the pattern card at `index` if it is still on sale and affordable
+fn on_buy_pattern_card(
+    trigger: On<BuyPatternCardEvent>,
+    mut offers: ResMut<ShopOffers>,
+    mut levels: ResMut<PatternLevels>,
+    mut game_state: ResMut<GameState>,
+) {
+    let Some(card) = offers.cards.get_mut(trigger.event().index) else {
+        return;
+    };
+    if card.sold || game_state.money < PATTERN_CARD_PRICE {
+        return;
+    }
+    card.sold = true;
+    game_state.money -= PATTERN_CARD_PRICE;
+    levels.level_up(card.pattern);
+}
+
+/// Observer: buy the joker at `index` if it is still on sale, affordable and a slot is free
+fn on_buy_joker(
+    trigger: On<BuyJokerEvent>,
+    mut offers: ResMut<ShopOffers>,
+    mut jokers: ResMut<Jokers>,
+    mut game_state: ResMut<GameState>,
+) {
+    let Some(offer) = offers.jokers.get_mut(trigger.event().index) else {
+        return;
+    };
+    if offer.sold || game_state.money < JOKER_PRICE || jokers.is_full() {
+        return;
+    }
+    offer.sold = true;
+    game_state.money -= JOKER_PRICE;
+    jokers.slots.push(offer.joker);
+}
+
+/// Observer: sell the joker in `slot` unless it is eternal
+fn on_sell_joker(
+    trigger: On<SellJokerEvent>,
+    mut jokers: ResMut<Jokers>,
+    mut game_state: ResMut<GameState>,
+) {
+    let slot = trigger.event().slot;
+    if jokers.slots.get(slot).is_none_or(|joker| joker.eternal) {
+        return;
+    }
+    jokers.slots.remove(slot);
+    game_state.money += JOKER_SELL_PRICE;
+}
+
+/// Observer: buy 悔牌 +1 once per shop visit, raising the undo limit
+fn on_buy_undo_perk(
+    _trigger: On<BuyUndoPerkEvent>,
+    mut offers: ResMut<ShopOffers>,
+    mut history: ResMut<ActionHistory>,
+    mut game_state: ResMut<GameState>,
+) {
+    if offers.undo_perk_sold || game_state.money < UNDO_PERK_PRICE {
+        return;
+    }
+    offers.undo_perk_sold = true;
+    game_state.money -= UNDO_PERK_PRICE;
+    history.raise_limit();
+}
+
+/// Observer: leave the shop and start the next blind
+fn on_leave_shop(_trigger: On<LeaveShopEvent>, mut next_phase: ResMut<NextState<PlayPhase>>) {
+    next_phase.set(PlayPhase::Selecting);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugins::board::DEFAULT_UNDO_LIMIT;
+
+    fn shop_world(joker: Joker) -> World {
+        let mut world = World::new();
+        world.insert_resource(GameState {
+            money: 20,
+            ..GameState::new(Stake::Black)
+        });
+        world.insert_resource(ShopOffers {
+            cards: Vec::new(),
+            jokers: vec![ShopJoker { joker, sold: false }],
+            undo_perk_sold: false,
+        });
+        world.init_resource::<Jokers>();
+        world.init_resource::<ActionHistory>();
+        world.add_observer(on_buy_joker);
+        world.add_observer(on_sell_joker);
+        world.add_observer(on_buy_undo_perk);
+        world
+    }
+
+    #[test]
+    fn test_buy_and_sell_joker() {
+        let joker = Joker {
+            kind: JokerKind::Dragon,
+            eternal: false,
+        };
+        let mut world = shop_world(joker);
+        world.trigger(BuyJokerEvent { index: 0 });
+        world.trigger(BuyJokerEvent { index: 0 });
+        world.flush();
+        assert_eq!(world.resource::<Jokers>().slots, vec![joker]);
+        assert_eq!(world.resource::<GameState>().money, 20 - JOKER_PRICE);
+
+        world.trigger(SellJokerEvent { slot: 0 });
+        world.flush();
+        assert!(world.resource::<Jokers>().slots.is_empty());
+        assert_eq!(
+            world.resource::<GameState>().money,
+            20 - JOKER_PRICE + JOKER_SELL_PRICE
+        );
+    }
+
+    #[test]
+    fn test_undo_perk_raises_limit() {
+        let mut world = shop_world(Joker {
+            kind: JokerKind::Dragon,
+            eternal: false,
+        });
+        world.trigger(BuyUndoPerkEvent);
+        // Only one per shop visit
+        world.trigger(BuyUndoPerkEvent);
+        world.flush();
+        let history = world.resource::<ActionHistory>();
+        assert_eq!(history.limit, DEFAULT_UNDO_LIMIT + 1);
+        assert_eq!(history.undos_left, DEFAULT_UNDO_LIMIT + 1);
+        assert_eq!(world.resource::<GameState>().money, 20 - UNDO_PERK_PRICE);
+        assert!(world.resource::<ShopOffers>().undo_perk_sold);
+    }
+
+    #[test]
+    fn test_eternal_joker_cannot_be_sold() {
+        let joker = Joker {
+            kind: JokerKind::Flush,
+            eternal: true,
+        };
+        let mut world = shop_world(joker);
+        world.trigger(BuyJokerEvent { index: 0 });
+        world.trigger(SellJokerEvent { slot: 0 });
+        world.flush();
+        assert_eq!(world.resource::<Jokers>().slots, vec![joker]);
+        assert_eq!(world.resource::<GameState>().money, 20 - JOKER_PRICE);
+    }
+}
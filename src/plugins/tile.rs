@@ -1,9 +1,10 @@
 use bevy::prelude::*;
-use rand::seq::SliceRandom;
+use rand::seq::{index, SliceRandom};
 use rand::thread_rng;
 
 use crate::components::tile::*;
-use crate::plugins::game::AppState;
+use crate::plugins::game::{AppState, PlayPhase};
+use crate::plugins::tile_faces::TileFaces;
 use crate::resources::*;
 
 pub const TILE_WIDTH: f32 = 48.0;
@@ -19,6 +20,30 @@ pub const HAND_Y: f32 = -260.0;
 // Play area center: approximately Y = 40
 pub const BOARD_Y: f32 = 40.0;
 pub const BOARD_START_X: f32 = 0.0;
+/// Revealed flowers and seasons sit in a row above the board
+pub const BONUS_Y: f32 = BOARD_Y + TILE_HEIGHT + 24.0;
+/// Extra space between declared melds on the board
+pub const MELD_GAP: f32 = 16.0;
+
+/// The wall display on the right of the table, where drawn tiles slide in from
+pub const WALL_POS: Vec3 = Vec3::new(580.0, BOARD_Y, 2.0);
+
+/// Seconds to slide between spots, to draw a tile from the wall,
+/// and to arc a played tile onto the board
+const SLIDE_SECONDS: f32 = 0.15;
+const DRAW_SECONDS: f32 = 0.35;
+const PLAY_SECONDS: f32 = 0.4;
+/// How high a played tile arcs on its way to the board
+const PLAY_ARC_HEIGHT: f32 = 80.0;
+/// Hovered tiles bob this high, this many times a second
+const HOVER_BOB_HEIGHT: f32 = 4.0;
+const HOVER_BOB_RATE: f32 = 2.0;
+/// Seconds for a discarded tile to flip over and drop away
+const DISCARD_SECONDS: f32 = 0.3;
+const DISCARD_DROP: f32 = 30.0;
+
+/// Number of tiles in each fresh set that start out enhanced
+const ENHANCED_TILE_COUNT: usize = 8;
 
 pub struct TilePlugin;
 
@@ -27,11 +52,22 @@ impl Plugin for TilePlugin {
         app.init_resource::<TileWall>()
             .init_resource::<PlayerHand>()
             .init_resource::<PlayBoard>()
-            .add_systems(OnEnter(AppState::Playing), spawn_tiles)
+            .init_resource::<SortMode>()
+            .add_systems(OnEnter(AppState::Playing), deal_tiles)
+            // Every blind after the first starts from a fresh wall
+            .add_systems(
+                OnTransition {
+                    exited: PlayPhase::Shop,
+                    entered: PlayPhase::Selecting,
+                },
+                deal_tiles,
+            )
             .add_systems(OnExit(AppState::Playing), cleanup_tiles)
             .add_systems(
                 Update,
-                update_tile_positions.run_if(in_state(AppState::Playing)),
+                (update_tile_positions, tween_tiles, flip_discarded)
+                    .chain()
+                    .run_if(in_state(AppState::Playing)),
             );
     }
 }
@@ -39,53 +75,85 @@ impl Plugin for TilePlugin {
 #[derive(Component)]
 pub struct TileText;
 
-fn spawn_tiles(
+/// Shuffle a fresh set into the wall, replacing any tiles from the last blind,
+/// and draw the opening hand
+fn deal_tiles(
     mut commands: Commands,
-    asset_server: Res<AssetServer>,
+    old_tiles: Query<Entity, With<Tile>>,
     mut wall: ResMut<TileWall>,
     mut hand: ResMut<PlayerHand>,
     mut play_board: ResMut<PlayBoard>,
     game_state: Option<Res<GameState>>,
+    faces: Option<Res<TileFaces>>,
 ) {
-    let hand_size = game_state.map(|gs| gs.hand_size).unwrap_or(8);
-    let font = asset_server.load("fonts/pixel.ttf");
+    let hand_size = game_state.as_ref().map(|gs| gs.hand_size).unwrap_or(8);
+    let ruleset = game_state.map(|gs| gs.ruleset).unwrap_or_default();
 
-    let mut tiles = Tile::generate_full_set();
-    tiles.shuffle(&mut thread_rng());
+    let mut rng = thread_rng();
+    let mut tiles = ruleset.rules().generate_deck();
+    tiles.shuffle(&mut rng);
+    let enhanced = index::sample(&mut rng, tiles.len(), ENHANCED_TILE_COUNT).into_vec();
 
-    wall.tiles.clear();
+    for entity in &old_tiles {
+        commands.entity(entity).despawn();
+    }
+    wall.clear();
     hand.tiles.clear();
-    play_board.tiles.clear();
+    play_board.clear();
 
-    for tile_data in tiles {
-        let label = tile_data.id.label();
+    for (i, tile_data) in tiles.into_iter().enumerate() {
+        let face = faces.as_ref().and_then(|faces| faces.get(tile_data.id));
+        let label = tile_data.id.short_label();
         let color = tile_data.id.suit_color();
+        let enhancement = enhanced
+            .contains(&i)
+            .then(|| *TileEnhancement::ALL.choose(&mut rng).unwrap());
+        let size = Vec2::new(TILE_WIDTH, TILE_HEIGHT);
+        // A painted face is tinted by its enhancement; without one the tile
+        // is a plain rect in the enhancement's color with a short label,
+        // as the default font has no CJK glyphs
+        let sprite = match &face {
+            Some(image) => Sprite {
+                image: image.clone(),
+                color: enhancement.map(|e| e.tint()).unwrap_or(Color::WHITE),
+                custom_size: Some(size),
+                ..default()
+            },
+            None => Sprite::from_color(
+                enhancement
+                    .map(|e| e.tint())
+                    .unwrap_or(Color::srgb(0.95, 0.92, 0.85)),
+                size,
+            ),
+        };
 
-        let entity = commands
-            .spawn((
-                tile_data,
-                TileLocation::Wall,
-                Sprite::from_color(
-                    Color::srgb(0.95, 0.92, 0.85),
-                    Vec2::new(TILE_WIDTH, TILE_HEIGHT),
-                ),
-                Transform::from_translation(Vec3::new(0.0, 0.0, 1.0)),
-                Visibility::Hidden,
-            ))
-            .with_children(|parent| {
+        let mut tile_commands = commands.spawn((
+            tile_data,
+            TileLocation::Wall,
+            sprite,
+            Transform::from_translation(Vec3::new(0.0, 0.0, 1.0)),
+            Visibility::Hidden,
+            Pickable::default(),
+            TileTween::default(),
+        ));
+        if let Some(enhancement) = enhancement {
+            tile_commands.insert(enhancement);
+        }
+        if face.is_none() {
+            tile_commands.with_children(|parent| {
                 parent.spawn((
                     TileText,
                     Text2d::new(label),
                     TextFont {
-                        font: font.clone(),
                         font_size: 16.0,
                         ..default()
                     },
                     TextColor(color),
                     Transform::from_translation(Vec3::new(0.0, 0.0, 0.1)),
                 ));
-            })
-            .id();
+            });
+        }
+        let entity = tile_commands.id();
 
         wall.tiles.push(entity);
     }
@@ -93,29 +161,45 @@ fn spawn_tiles(
     // Draw initial hand
     let draw_count = hand_size.min(wall.tiles.len());
     for _ in 0..draw_count {
-        if let Some(entity) = wall.tiles.pop() {
+        if let Some(entity) = wall.draw() {
             hand.tiles.push(entity);
         }
     }
 }
 
 fn update_tile_positions(
+    sort_mode: Res<SortMode>,
     mut hand: ResMut<PlayerHand>,
     mut play_board: ResMut<PlayBoard>,
     tile_q: Query<&Tile>,
     mut query: Query<(
-        &mut Transform,
+        &mut TileTween,
         &mut Visibility,
         &mut TileLocation,
         Has<TileSelected>,
     )>,
+    dragged_q: Query<(), With<Dragging>>,
 ) {
-    // Sort hand and board tiles by suit then value
-    hand.tiles
-        .sort_by_key(|e| tile_q.get(*e).map(|t| t.id).ok());
-    play_board
-        .tiles
-        .sort_by_key(|e| tile_q.get(*e).map(|t| t.id).ok());
+    // Sort the hand by the chosen sort mode (unless arranged by hand)
+    // and board tiles by suit then value;
+    // declared melds stay in play order, sorted within each meld
+    sort_mode.arrange(&mut hand.tiles, |e| tile_q.get(*e).ok().map(|t| t.id));
+    if play_board.melds.is_empty() {
+        play_board
+            .tiles
+            .sort_by_key(|e| tile_q.get(*e).map(|t| t.id).ok());
+    } else {
+        for declared in &mut play_board.melds {
+            declared
+                .tiles
+                .sort_by_key(|e| tile_q.get(*e).map(|t| t.id).ok());
+        }
+        play_board.tiles = play_board
+            .melds
+            .iter()
+            .flat_map(|d| d.tiles.iter().copied())
+            .collect();
+    }
 
     // Update hand tile positions
     let hand_count = hand.tiles.len();
@@ -123,25 +207,45 @@ fn update_tile_positions(
     let hand_start_x = -hand_total_width / 2.0 + TILE_WIDTH / 2.0;
 
     for (i, &entity) in hand.tiles.iter().enumerate() {
-        if let Ok((mut transform, mut vis, mut loc, selected)) = query.get_mut(entity) {
+        if let Ok((mut tween, mut vis, mut loc, selected)) = query.get_mut(entity) {
             *loc = TileLocation::Hand;
             *vis = Visibility::Inherited;
+            if dragged_q.contains(entity) {
+                continue;
+            }
             let x = hand_start_x + i as f32 * (TILE_WIDTH + TILE_GAP);
             let y_offset = if selected { 15.0 } else { 0.0 };
-            transform.translation = Vec3::new(x, HAND_Y + y_offset, 2.0);
+            tween.home = Vec3::new(x, HAND_Y + y_offset, 2.0);
         }
     }
 
-    // Update board tile positions
-    let board_total_width = 14.0_f32 * (TILE_WIDTH + TILE_GAP) - TILE_GAP;
+    // Update board tile positions; declared melds are centered as separate groups
+    let (board_x, board_total_width) = if play_board.melds.is_empty() {
+        let offsets = (0..play_board.tiles.len())
+            .map(|i| i as f32 * (TILE_WIDTH + TILE_GAP))
+            .collect();
+        (offsets, 14.0_f32 * (TILE_WIDTH + TILE_GAP) - TILE_GAP)
+    } else {
+        meld_offsets(&play_board.melds)
+    };
     let board_start_x = -board_total_width / 2.0 + TILE_WIDTH / 2.0 + BOARD_START_X;
 
-    for (i, &entity) in play_board.tiles.iter().enumerate() {
-        if let Ok((mut transform, mut vis, mut loc, _)) = query.get_mut(entity) {
+    for (&entity, &offset) in play_board.tiles.iter().zip(&board_x) {
+        if let Ok((mut tween, mut vis, mut loc, ..)) = query.get_mut(entity) {
             *loc = TileLocation::Board;
             *vis = Visibility::Inherited;
-            let x = board_start_x + i as f32 * (TILE_WIDTH + TILE_GAP);
-            transform.translation = Vec3::new(x, BOARD_Y, 2.0);
+            tween.home = Vec3::new(board_start_x + offset, BOARD_Y, 2.0);
+        }
+    }
+
+    let bonus_total_width = play_board.bonus.len() as f32 * (TILE_WIDTH + TILE_GAP) - TILE_GAP;
+    let bonus_start_x = -bonus_total_width / 2.0 + TILE_WIDTH / 2.0;
+    for (i, &entity) in play_board.bonus.iter().enumerate() {
+        if let Ok((mut tween, mut vis, mut loc, ..)) = query.get_mut(entity) {
+            *loc = TileLocation::Bonus;
+            *vis = Visibility::Inherited;
+            let x = bonus_start_x + i as f32 * (TILE_WIDTH + TILE_GAP);
+            tween.home = Vec3::new(x, BONUS_Y, 2.0);
         }
     }
 
@@ -149,6 +253,110 @@ fn update_tile_positions(
     // (wall tiles remain hidden by default since they spawn hidden)
 }
 
+/// Slide tiles toward their resting spots: drawn tiles come in from the
+/// wall, played tiles arc onto the board, and tiles knocked out of place
+/// (dropped, or left mid-bob) settle back. Hovered tiles bob.
+fn tween_tiles(
+    time: Res<Time>,
+    mut query: Query<
+        (
+            &mut Transform,
+            &mut TileTween,
+            &TileLocation,
+            Has<TileHovered>,
+        ),
+        Without<Dragging>,
+    >,
+) {
+    let bob = HOVER_BOB_HEIGHT
+        * (time.elapsed_secs() * HOVER_BOB_RATE * std::f32::consts::PI)
+            .sin()
+            .abs();
+    for (mut transform, mut tween, location, hovered) in &mut query {
+        // Flipping away in `flip_discarded`
+        if *location == TileLocation::Discarded {
+            tween.location = *location;
+            continue;
+        }
+        if tween.home != tween.to {
+            let (from, duration, arc) = match (tween.location, *location) {
+                (TileLocation::Wall, TileLocation::Hand) => (WALL_POS, DRAW_SECONDS, 0.0),
+                (TileLocation::Hand, TileLocation::Board) => {
+                    (transform.translation, PLAY_SECONDS, PLAY_ARC_HEIGHT)
+                }
+                _ => (transform.translation, SLIDE_SECONDS, 0.0),
+            };
+            tween.start(from, duration, arc);
+        } else if tween.is_done() && !hovered && transform.translation.distance(tween.to) > 0.5 {
+            let from = transform.translation;
+            tween.start(from, SLIDE_SECONDS, 0.0);
+        }
+
+        tween.elapsed = (tween.elapsed + time.delta_secs()).min(tween.duration);
+        let mut position = tween.position();
+        if hovered && tween.is_done() {
+            position.y += bob;
+        }
+        if transform.translation != position {
+            transform.translation = position;
+        }
+        tween.location = *location;
+    }
+}
+
+/// Discarded tiles squash flat as if flipping face down, drop, then hide
+fn flip_discarded(
+    time: Res<Time>,
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut Transform, &mut Discarding, &mut Visibility)>,
+) {
+    for (entity, mut transform, mut discarding, mut visibility) in &mut query {
+        let step = time.delta_secs().min(DISCARD_SECONDS - discarding.elapsed);
+        discarding.elapsed += step;
+        transform.translation.y -= DISCARD_DROP * step / DISCARD_SECONDS;
+        let t = discarding.elapsed / DISCARD_SECONDS;
+        transform.scale.x = (1.0 - t).max(0.0);
+        if t >= 1.0 {
+            transform.scale = Vec3::ONE;
+            *visibility = Visibility::Hidden;
+            commands.entity(entity).remove::<Discarding>();
+        }
+    }
+}
+
+/// X offset of each declared meld tile from the board's left edge,
+/// with a gap between melds, and the total width
+pub fn meld_offsets(melds: &[DeclaredMeld]) -> (Vec<f32>, f32) {
+    let mut offsets = Vec::new();
+    let mut x = 0.0;
+    for (m, declared) in melds.iter().enumerate() {
+        if m > 0 {
+            x += MELD_GAP;
+        }
+        for _ in &declared.tiles {
+            offsets.push(x);
+            x += TILE_WIDTH + TILE_GAP;
+        }
+    }
+    (offsets, x - TILE_GAP)
+}
+
+/// World x of the center of each meld on the board
+pub fn meld_centers(melds: &[DeclaredMeld]) -> Vec<f32> {
+    let (offsets, width) = meld_offsets(melds);
+    let start_x = -width / 2.0 + TILE_WIDTH / 2.0 + BOARD_START_X;
+    let mut first = 0;
+    melds
+        .iter()
+        .map(|declared| {
+            let last = first + declared.tiles.len() - 1;
+            let center = start_x + (offsets[first] + offsets[last]) / 2.0;
+            first = last + 1;
+            center
+        })
+        .collect()
+}
+
 fn cleanup_tiles(
     mut commands: Commands,
     tiles: Query<Entity, With<Tile>>,
@@ -159,7 +367,45 @@ fn cleanup_tiles(
     for entity in &tiles {
         commands.entity(entity).despawn();
     }
-    wall.tiles.clear();
+    wall.clear();
     hand.tiles.clear();
-    board.tiles.clear();
+    board.clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugins::ruleset::RulesetKind;
+    use bevy::ecs::system::RunSystemOnce;
+
+    #[test]
+    fn test_fresh_wall_every_blind() {
+        let mut world = World::new();
+        world.insert_resource(GameState {
+            ruleset: RulesetKind::Sichuan,
+            ..GameState::new(Stake::White)
+        });
+        world.init_resource::<TileWall>();
+        world.init_resource::<PlayerHand>();
+        world.init_resource::<PlayBoard>();
+        let deck = RulesetKind::Sichuan.rules().generate_deck().len();
+
+        // Each blind empties the wall; the next one must still deal in full
+        let blinds = 3 * DEFAULT_FINAL_LEVEL;
+        for blind in 1..=blinds {
+            world.run_system_once(deal_tiles).unwrap();
+            world.flush();
+            let hand_size = world.resource::<GameState>().hand_size;
+            assert_eq!(world.resource::<PlayerHand>().tiles.len(), hand_size);
+            assert_eq!(world.resource::<TileWall>().tiles.len(), deck - hand_size);
+            let spawned = world.query::<&Tile>().iter(&world).count();
+            assert_eq!(spawned, deck);
+
+            let mut wall = world.resource_mut::<TileWall>();
+            while wall.draw().is_some() {}
+            let more = world.resource_mut::<GameState>().advance_sub_round();
+            assert_eq!(more, blind < blinds);
+        }
+        assert_eq!(world.resource::<GameState>().level, DEFAULT_FINAL_LEVEL);
+    }
 }
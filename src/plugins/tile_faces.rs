@@ -0,0 +1,520 @@
+use std::collections::HashMap;
+
+use bevy::asset::RenderAssetUsages;
+use bevy::image::ImageSampler;
+use bevy::prelude::*;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+
+use crate::components::tile::*;
+
+/// Faces are drawn at half the tile's on-screen size and scaled up 2×
+const FACE_WIDTH: usize = 24;
+const FACE_HEIGHT: usize = 32;
+/// Rows 1..=28 hold the face; below them the tile's green back shows
+const BACK_TOP: usize = 29;
+
+const FACE_COLOR: Color = Color::srgb(0.95, 0.92, 0.85);
+const EDGE_COLOR: Color = Color::srgb(0.55, 0.5, 0.42);
+const BACK_COLOR: Color = Color::srgb(0.2, 0.5, 0.35);
+const INK_COLOR: Color = Color::srgb(0.12, 0.12, 0.15);
+const PIP_CENTER_COLOR: Color = Color::srgb(0.85, 0.2, 0.2);
+const STICK_NODE_COLOR: Color = Color::srgb(0.1, 0.4, 0.2);
+
+pub struct TileFacesPlugin;
+
+impl Plugin for TileFacesPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, generate_tile_faces);
+    }
+}
+
+/// Generated face image for every tile kind, flowers and seasons included
+#[derive(Resource, Default)]
+pub struct TileFaces {
+    faces: HashMap<TileId, Handle<Image>>,
+}
+
+impl TileFaces {
+    pub fn get(&self, id: TileId) -> Option<Handle<Image>> {
+        self.faces.get(&id).cloned()
+    }
+}
+
+fn generate_tile_faces(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
+    let faces = (0..42)
+        .map(TileId::from_index)
+        .map(|id| (id, images.add(paint_face(id).into_image())))
+        .collect();
+    commands.insert_resource(TileFaces { faces });
+}
+
+/// RGBA pixels, row by row from the top left
+struct Canvas {
+    pixels: Vec<[u8; 4]>,
+}
+
+impl Canvas {
+    /// A blank tile: face, edge, the green back along the bottom and clipped corners
+    fn blank_tile() -> Self {
+        let mut canvas = Canvas {
+            pixels: vec![[0; 4]; FACE_WIDTH * FACE_HEIGHT],
+        };
+        let (right, bottom) = (FACE_WIDTH - 1, FACE_HEIGHT - 1);
+        for y in 0..FACE_HEIGHT {
+            for x in 0..FACE_WIDTH {
+                let corner = (x == 0 || x == right) && (y == 0 || y == bottom);
+                let color = if corner {
+                    continue;
+                } else if x == 0 || x == right || y == 0 || y == bottom {
+                    EDGE_COLOR
+                } else if y >= BACK_TOP {
+                    BACK_COLOR
+                } else {
+                    FACE_COLOR
+                };
+                canvas.set(x as i32, y as i32, color);
+            }
+        }
+        canvas
+    }
+
+    fn set(&mut self, x: i32, y: i32, color: Color) {
+        if (0..FACE_WIDTH as i32).contains(&x) && (0..FACE_HEIGHT as i32).contains(&y) {
+            self.pixels[y as usize * FACE_WIDTH + x as usize] = color.to_srgba().to_u8_array();
+        }
+    }
+
+    fn rect(&mut self, x: i32, y: i32, width: i32, height: i32, color: Color) {
+        for py in y..y + height {
+            for px in x..x + width {
+                self.set(px, py, color);
+            }
+        }
+    }
+
+    fn disc(&mut self, cx: i32, cy: i32, radius: f32, color: Color) {
+        let reach = radius.ceil() as i32;
+        for dy in -reach..=reach {
+            for dx in -reach..=reach {
+                if ((dx * dx + dy * dy) as f32) <= (radius + 0.5).powi(2) {
+                    self.set(cx + dx, cy + dy, color);
+                }
+            }
+        }
+    }
+
+    /// 筒 pip: a ring with a red center
+    fn pip(&mut self, cx: i32, cy: i32, radius: f32, color: Color) {
+        self.disc(cx, cy, radius, color);
+        if radius >= 2.0 {
+            self.disc(cx, cy, radius - 1.0, FACE_COLOR);
+        }
+        self.disc(cx, cy, (radius / 3.0).floor(), PIP_CENTER_COLOR);
+    }
+
+    /// 条 stick: a bamboo segment with darker nodes at its ends and middle
+    fn stick(&mut self, cx: i32, cy: i32, width: i32, height: i32, color: Color) {
+        let (x, y) = (cx - width / 2, cy - height / 2);
+        self.rect(x, y, width, height, color);
+        for node in [y, y + height / 2, y + height - 1] {
+            self.rect(x, node, width, 1, STICK_NODE_COLOR);
+        }
+    }
+
+    /// Draw a bitmap glyph, `#` marking ink, each bitmap pixel `scale` pixels wide
+    fn glyph(&mut self, rows: &[&str], x: i32, y: i32, scale: i32, color: Color) {
+        for (gy, row) in rows.iter().enumerate() {
+            for (gx, cell) in row.chars().enumerate() {
+                if cell == '#' {
+                    self.rect(
+                        x + gx as i32 * scale,
+                        y + gy as i32 * scale,
+                        scale,
+                        scale,
+                        color,
+                    );
+                }
+            }
+        }
+    }
+
+    fn into_image(self) -> Image {
+        let mut image = Image::new(
+            Extent3d {
+                width: FACE_WIDTH as u32,
+                height: FACE_HEIGHT as u32,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            self.pixels.into_iter().flatten().collect(),
+            TextureFormat::Rgba8UnormSrgb,
+            RenderAssetUsages::RENDER_WORLD,
+        );
+        image.sampler = ImageSampler::nearest();
+        image
+    }
+}
+
+/// Paint a tile's face
+fn paint_face(id: TileId) -> Canvas {
+    let mut canvas = Canvas::blank_tile();
+    let color = id.suit_color();
+    match id.suit {
+        TileSuit::Pin => {
+            let radius = match id.value {
+                1 => 6.0,
+                2..=6 => 3.0,
+                _ => 2.0,
+            };
+            for &(x, y) in PIP_LAYOUTS[id.value as usize - 1] {
+                canvas.pip(x, y, radius, color);
+            }
+        }
+        TileSuit::Sou if id.value == 1 => canvas.stick(12, 14, 4, 21, color),
+        TileSuit::Sou => {
+            for &(x, y) in STICK_LAYOUTS[id.value as usize - 1] {
+                canvas.stick(x, y, 2, 7, color);
+            }
+        }
+        TileSuit::Man => {
+            canvas.glyph(NUMERALS[id.value as usize - 1], 5, 1, 2, INK_COLOR);
+            canvas.glyph(WAN, 5, 15, 2, color);
+        }
+        TileSuit::Wind => canvas.glyph(WINDS[id.value as usize - 1], 3, 6, 2, color),
+        TileSuit::Dragon if id.value == 3 => {
+            // 白: an empty frame
+            canvas.rect(5, 5, 14, 20, color);
+            canvas.rect(7, 7, 10, 16, FACE_COLOR);
+        }
+        TileSuit::Dragon => canvas.glyph(DRAGONS[id.value as usize - 1], 3, 6, 2, color),
+        TileSuit::Flower => canvas.glyph(FLOWERS[id.value as usize - 1], 3, 6, 2, color),
+        TileSuit::Season => canvas.glyph(SEASONS[id.value as usize - 1], 3, 6, 2, color),
+    }
+    canvas
+}
+
+/// Pip centers for 1–9 筒
+const PIP_LAYOUTS: [&[(i32, i32)]; 9] = [
+    &[(12, 14)],
+    &[(12, 7), (12, 21)],
+    &[(6, 5), (12, 14), (18, 23)],
+    &[(7, 7), (17, 7), (7, 21), (17, 21)],
+    &[(7, 7), (17, 7), (12, 14), (7, 21), (17, 21)],
+    &[(7, 5), (17, 5), (7, 14), (17, 14), (7, 23), (17, 23)],
+    &[
+        (5, 4),
+        (12, 8),
+        (19, 12),
+        (7, 18),
+        (17, 18),
+        (7, 24),
+        (17, 24),
+    ],
+    &[
+        (7, 4),
+        (17, 4),
+        (7, 10),
+        (17, 10),
+        (7, 17),
+        (17, 17),
+        (7, 24),
+        (17, 24),
+    ],
+    &[
+        (6, 5),
+        (12, 5),
+        (18, 5),
+        (6, 14),
+        (12, 14),
+        (18, 14),
+        (6, 23),
+        (12, 23),
+        (18, 23),
+    ],
+];
+
+/// Stick centers for 2–9 条 (1 条 is a single tall stick)
+const STICK_LAYOUTS: [&[(i32, i32)]; 9] = [
+    &[],
+    &[(12, 8), (12, 20)],
+    &[(12, 8), (7, 20), (17, 20)],
+    &[(7, 8), (17, 8), (7, 20), (17, 20)],
+    &[(7, 8), (17, 8), (12, 14), (7, 20), (17, 20)],
+    &[(6, 8), (12, 8), (18, 8), (6, 20), (12, 20), (18, 20)],
+    &[
+        (12, 5),
+        (6, 14),
+        (12, 14),
+        (18, 14),
+        (6, 23),
+        (12, 23),
+        (18, 23),
+    ],
+    &[
+        (5, 8),
+        (10, 8),
+        (15, 8),
+        (20, 8),
+        (5, 20),
+        (10, 20),
+        (15, 20),
+        (20, 20),
+    ],
+    &[
+        (6, 5),
+        (12, 5),
+        (18, 5),
+        (6, 14),
+        (12, 14),
+        (18, 14),
+        (6, 23),
+        (12, 23),
+        (18, 23),
+    ],
+];
+
+/// 一 to 九, 7×7
+const NUMERALS: [&[&str]; 9] = [
+    &[
+        ".......", ".......", ".......", "#######", ".......", ".......", ".......",
+    ],
+    &[
+        ".......", ".#####.", ".......", ".......", ".......", "#######", ".......",
+    ],
+    &[
+        ".#####.", ".......", ".......", "..###..", ".......", ".......", "#######",
+    ],
+    &[
+        "#######", "#.#.#.#", "#.#.#.#", "#.#..##", "##....#", "#.....#", "#######",
+    ],
+    &[
+        "#######", "...#...", "...#...", ".#####.", ".#...#.", ".#...#.", "#######",
+    ],
+    &[
+        "...#...", "....#..", "#######", ".......", "..#.#..", ".#...#.", "#.....#",
+    ],
+    &[
+        "..#....", "..#...#", "..#.##.", ".###...", "#.#....", "..#...#", "...####",
+    ],
+    &[
+        "..#.#..", "..#.#..", "..#.#..", "..#..#.", ".#...#.", ".#....#", "#.....#",
+    ],
+    &[
+        "..#....", "#####..", "..#.#..", "..#.#..", ".#..#..", ".#..#.#", "#...###",
+    ],
+];
+
+/// 万, 7×7
+const WAN: &[&str] = &[
+    "#######", "..#....", "..#####", "..#...#", ".#....#", ".#....#", "#...##.",
+];
+
+/// 东南西北, 9×9
+const WINDS: [&[&str]; 4] = [
+    &[
+        "....#....",
+        "#########",
+        "...#.....",
+        "..#..#...",
+        ".######..",
+        ".....#...",
+        ".#...#.#.",
+        "#....#..#",
+        "....##...",
+    ],
+    &[
+        "....#....",
+        "#########",
+        "....#....",
+        "#########",
+        "#.#...#.#",
+        "#..#.#..#",
+        "#.#####.#",
+        "#...#...#",
+        "#...#..##",
+    ],
+    &[
+        "#########",
+        "...#.#...",
+        ".#######.",
+        ".#.#.#.#.",
+        ".#.#.#.#.",
+        ".##...##.",
+        ".#.....#.",
+        ".#######.",
+        ".#.....#.",
+    ],
+    &[
+        "...#.#...",
+        "...#.#...",
+        "...#.#..#",
+        "####.#.#.",
+        "...#.##..",
+        "...#.#...",
+        "..##.#...",
+        ".#.#.#..#",
+        "#..#..###",
+    ],
+];
+
+/// 中发, 9×9 (白 is drawn as a frame)
+const DRAGONS: [&[&str]; 2] = [
+    &[
+        "....#....",
+        "....#....",
+        "#########",
+        "#...#...#",
+        "#...#...#",
+        "#########",
+        "....#....",
+        "....#....",
+        "....#....",
+    ],
+    &[
+        "..#...#..",
+        ".#.#.#...",
+        "#########",
+        "...#.....",
+        "..#####..",
+        "..#..#...",
+        ".#.#.#...",
+        ".#..#....",
+        "#..#.##..",
+    ],
+];
+
+/// 梅兰竹菊, 9×9
+const FLOWERS: [&[&str]; 4] = [
+    &[
+        ".#..#....",
+        ".#.######",
+        "####.#...",
+        ".#.######",
+        "##.#.#.#.",
+        "##.######",
+        ".#.#.#.#.",
+        ".#.######",
+        ".#.....##",
+    ],
+    &[
+        "..#...#..",
+        "...#.#...",
+        "#########",
+        ".........",
+        ".#######.",
+        ".........",
+        ".........",
+        "#########",
+        ".........",
+    ],
+    &[
+        ".#....#..",
+        ".#####.##",
+        "#.#.##.#.",
+        "..#...#..",
+        ".###.####",
+        "#.#.#.#..",
+        "..#...#..",
+        "..#...#..",
+        ".##..##..",
+    ],
+    &[
+        "..#...#..",
+        "#########",
+        "..#...#..",
+        ".#.......",
+        ".#######.",
+        "#.#.#.#.#",
+        ".#.###.#.",
+        ".#.#.#.#.",
+        ".#######.",
+    ],
+];
+
+/// 春夏秋冬, 9×9
+const SEASONS: [&[&str]; 4] = [
+    &[
+        "....#....",
+        ".#######.",
+        "....#....",
+        "#########",
+        "...#.#...",
+        "..#####..",
+        ".#.#.#.#.",
+        "#..###..#",
+        "...#.#...",
+    ],
+    &[
+        "#########",
+        "....#....",
+        ".#######.",
+        ".#.....#.",
+        ".#######.",
+        ".#.....#.",
+        ".#######.",
+        "..##.##..",
+        "##....###",
+    ],
+    &[
+        "..#..#...",
+        "###..#.#.",
+        "..#.#.#..",
+        "####.#...",
+        "..#..#...",
+        ".###.#.#.",
+        "#.#.#.#.#",
+        "..#.#..#.",
+        "..##....#",
+    ],
+    &[
+        "...#.....",
+        "..######.",
+        ".#.#..#..",
+        "#...##...",
+        "...#..##.",
+        ".##.....#",
+        "...##....",
+        "......#..",
+        ".......##",
+    ],
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_glyph(rows: &[&str], size: usize) {
+        assert_eq!(rows.len(), size);
+        assert!(
+            rows.iter().all(|row| row.chars().count() == size),
+            "{rows:?}"
+        );
+    }
+
+    #[test]
+    fn test_every_tile_kind_has_a_face() {
+        let blank = Canvas::blank_tile().pixels;
+        for index in 0..42 {
+            let id = TileId::from_index(index);
+            assert_ne!(paint_face(id).pixels, blank, "{id:?} painted nothing");
+        }
+    }
+
+    #[test]
+    fn test_face_tables() {
+        for (i, layout) in PIP_LAYOUTS.iter().enumerate() {
+            assert_eq!(layout.len(), i + 1, "{} 筒", i + 1);
+        }
+        // 1 条 is a single tall stick, not a layout entry
+        for (i, layout) in STICK_LAYOUTS.iter().enumerate().skip(1) {
+            assert_eq!(layout.len(), i + 1, "{} 条", i + 1);
+        }
+        for glyph in NUMERALS.iter().chain([&WAN]) {
+            assert_glyph(glyph, 7);
+        }
+        let honors = WINDS.iter().chain(&DRAGONS);
+        for glyph in honors.chain(&FLOWERS).chain(&SEASONS) {
+            assert_glyph(glyph, 9);
+        }
+    }
+}
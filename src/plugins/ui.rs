@@ -1,8 +1,13 @@
 use bevy::prelude::*;
 
 use crate::components::board::*;
+use crate::components::tile::{Tile, TileEnhancement, TileId, TileLocation, TileSuit};
 use crate::events::*;
-use crate::plugins::game::AppState;
+use crate::plugins::game::{AppState, PlayPhase};
+use crate::plugins::ruleset::RulesetKind;
+use crate::plugins::board::ActionHistory;
+use crate::plugins::scoring::SelectionPreview;
+use crate::plugins::shop::{ShopOffers, JOKER_PRICE, JOKER_SELL_PRICE, PATTERN_CARD_PRICE, UNDO_PERK_PRICE};
 use crate::resources::*;
 
 const BG_DARK: Color = Color::srgb(0.12, 0.12, 0.15);
@@ -12,30 +17,73 @@ const BG_BUTTON_HOVER: Color = Color::srgb(0.35, 0.35, 0.42);
 const BG_BUTTON_PRESS: Color = Color::srgb(0.45, 0.55, 0.45);
 const BORDER_COLOR: Color = Color::srgb(0.4, 0.4, 0.5);
 const TEXT_COLOR: Color = Color::srgb(0.9, 0.9, 0.85);
+const TEXT_DIM: Color = Color::srgb(0.5, 0.5, 0.55);
 const ACCENT_RED: Color = Color::srgb(0.85, 0.2, 0.2);
 
+const TOTAL_FONT_SIZE: f32 = 20.0;
+const TOTAL_SLAM_FONT_SIZE: f32 = 34.0;
+/// Tooltip offset from the pointer, so it doesn't cover what it describes
+const TOOLTIP_OFFSET: Vec2 = Vec2::new(16.0, 16.0);
+
 pub struct UiPlugin;
 
 impl Plugin for UiPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(AppState::Menu), setup_menu_ui)
+        app.add_observer(on_score_calculated)
+            .add_observer(on_play_rejected)
+            .add_observer(on_inspect_tile)
+            .add_observer(on_tooltip_over)
+            .add_observer(on_tooltip_out)
+            .init_resource::<HoveredTooltip>()
+            .add_systems(Startup, spawn_tooltip)
+            .add_systems(OnEnter(AppState::Menu), setup_menu_ui)
             .add_systems(OnExit(AppState::Menu), cleanup::<MenuRoot>)
             .add_systems(OnEnter(AppState::Playing), setup_game_ui)
             .add_systems(OnExit(AppState::Playing), cleanup::<GameUiRoot>)
             .add_systems(OnEnter(AppState::GameOver), setup_gameover_ui)
             .add_systems(OnExit(AppState::GameOver), cleanup::<GameOverRoot>)
+            .add_systems(OnEnter(PlayPhase::Shop), setup_shop_ui)
+            .add_systems(OnExit(PlayPhase::Shop), cleanup::<ShopRoot>)
+            .add_systems(OnEnter(AppState::Victory), setup_victory_ui)
+            .add_systems(OnExit(AppState::Victory), cleanup::<VictoryRoot>)
             .add_systems(
                 Update,
                 (
-                    menu_button_system.run_if(in_state(AppState::Menu)),
+                    (
+                        menu_button_system,
+                        stake_button_system,
+                        update_stake_display,
+                        play_mode_button_system,
+                        ruleset_button_system,
+                        run_length_button_system,
+                    )
+                        .run_if(in_state(AppState::Menu)),
                     (
                         game_button_system,
+                        riichi_button_system,
+                        sichuan_button_system,
+                        update_riichi_button,
+                        update_button_rows,
                         update_score_display,
                         update_wall_count,
+                        update_money_display,
+                        update_joker_display,
+                        update_wind_display,
+                        sort_button_system,
+                        update_sort_button,
+                        undo_button_system,
+                        update_undo_button,
+                        update_speed_button,
+                        update_preview_text,
+                        ease_total_score_text,
                     )
                         .run_if(in_state(AppState::Playing)),
+                    (shop_button_system, update_shop_display, update_shop_jokers)
+                        .run_if(in_state(PlayPhase::Shop)),
                     gameover_button_system.run_if(in_state(AppState::GameOver)),
+                    victory_button_system.run_if(in_state(AppState::Victory)),
                     button_hover_system,
+                    (update_tooltip_text, position_tooltip),
                 ),
             );
     }
@@ -51,6 +99,33 @@ struct GameUiRoot;
 #[derive(Component)]
 struct GameOverRoot;
 
+#[derive(Component)]
+struct VictoryRoot;
+
+#[derive(Component)]
+struct ShopRoot;
+
+/// Text shown next to the pointer while hovering this entity. Sprites and
+/// UI nodes also need `Pickable` to be hovered; tiles describe themselves.
+#[derive(Component, Clone, Default)]
+pub struct Tooltip(pub String);
+
+impl Tooltip {
+    pub fn new(text: impl Into<String>) -> Self {
+        Self(text.into())
+    }
+}
+
+#[derive(Component)]
+struct TooltipBox;
+
+#[derive(Component)]
+struct TooltipText;
+
+/// Entity under the pointer that has a tooltip, if any
+#[derive(Resource, Default)]
+struct HoveredTooltip(Option<Entity>);
+
 fn cleanup<T: Component>(mut commands: Commands, query: Query<Entity, With<T>>) {
     for entity in &query {
         commands.entity(entity).despawn();
@@ -59,8 +134,16 @@ fn cleanup<T: Component>(mut commands: Commands, query: Query<Entity, With<T>>)
 
 // ===================== MENU =====================
 
-fn setup_menu_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
+fn setup_menu_ui(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    profile: Res<Profile>,
+    play_mode: Res<PlayMode>,
+    ruleset: Res<RulesetKind>,
+    run_length: Res<RunLength>,
+) {
     let font = asset_server.load("fonts/pixel.ttf");
+    let stake = profile.selected_stake;
 
     commands
         .spawn((
@@ -99,6 +182,128 @@ fn setup_menu_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
                 TextColor(TEXT_COLOR),
             ));
 
+            // Stake selector
+            parent
+                .spawn(Node {
+                    flex_direction: FlexDirection::Row,
+                    align_items: AlignItems::Center,
+                    column_gap: Val::Px(20.0),
+                    ..default()
+                })
+                .with_children(|row| {
+                    spawn_stake_arrow(row, &font, "<", StakePrevButton);
+                    row.spawn((
+                        StakeText,
+                        Text::new(stake.label()),
+                        TextFont {
+                            font: font.clone(),
+                            font_size: 28.0,
+                            ..default()
+                        },
+                        TextColor(ACCENT_RED),
+                    ));
+                    spawn_stake_arrow(row, &font, ">", StakeNextButton);
+                });
+
+            parent.spawn((
+                StakePenaltyText,
+                Text::new(stake_penalty_summary(stake)),
+                TextFont {
+                    font: font.clone(),
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextColor(Color::srgb(0.6, 0.6, 0.65)),
+                TextLayout::new_with_justify(Justify::Center),
+            ));
+
+            // Play mode toggle
+            parent
+                .spawn((
+                    PlayModeButton,
+                    Button,
+                    Node {
+                        width: Val::Px(200.0),
+                        height: Val::Px(40.0),
+                        border: UiRect::all(Val::Px(2.0)),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    BorderColor::all(BORDER_COLOR),
+                    BackgroundColor(BG_BUTTON),
+                ))
+                .with_children(|btn| {
+                    btn.spawn((
+                        PlayModeText,
+                        Text::new(play_mode.label()),
+                        TextFont {
+                            font: font.clone(),
+                            font_size: 18.0,
+                            ..default()
+                        },
+                        TextColor(TEXT_COLOR),
+                    ));
+                });
+
+            // Ruleset selection
+            parent
+                .spawn((
+                    RulesetButton,
+                    Button,
+                    Node {
+                        width: Val::Px(200.0),
+                        height: Val::Px(40.0),
+                        border: UiRect::all(Val::Px(2.0)),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    BorderColor::all(BORDER_COLOR),
+                    BackgroundColor(BG_BUTTON),
+                ))
+                .with_children(|btn| {
+                    btn.spawn((
+                        RulesetText,
+                        Text::new(ruleset.label()),
+                        TextFont {
+                            font: font.clone(),
+                            font_size: 18.0,
+                            ..default()
+                        },
+                        TextColor(TEXT_COLOR),
+                    ));
+                });
+
+            // Run length selection
+            parent
+                .spawn((
+                    RunLengthButton,
+                    Button,
+                    Node {
+                        width: Val::Px(200.0),
+                        height: Val::Px(40.0),
+                        border: UiRect::all(Val::Px(2.0)),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    BorderColor::all(BORDER_COLOR),
+                    BackgroundColor(BG_BUTTON),
+                ))
+                .with_children(|btn| {
+                    btn.spawn((
+                        RunLengthText,
+                        Text::new(run_length.label()),
+                        TextFont {
+                            font: font.clone(),
+                            font_size: 18.0,
+                            ..default()
+                        },
+                        TextColor(TEXT_COLOR),
+                    ));
+                });
+
             // Start button
             parent
                 .spawn((
@@ -129,22 +334,162 @@ fn setup_menu_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
         });
 }
 
+fn spawn_stake_arrow(parent: &mut ChildSpawnerCommands, font: &Handle<Font>, label: &str, marker: impl Component) {
+    parent
+        .spawn((
+            marker,
+            Button,
+            Node {
+                width: Val::Px(40.0),
+                height: Val::Px(40.0),
+                border: UiRect::all(Val::Px(2.0)),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            BorderColor::all(BORDER_COLOR),
+            BackgroundColor(BG_BUTTON),
+        ))
+        .with_children(|btn| {
+            btn.spawn((
+                Text::new(label),
+                TextFont {
+                    font: font.clone(),
+                    font_size: 20.0,
+                    ..default()
+                },
+                TextColor(TEXT_COLOR),
+            ));
+        });
+}
+
+fn stake_penalty_summary(stake: Stake) -> String {
+    let penalties = stake.penalties();
+    if penalties.is_empty() {
+        "基础难度".into()
+    } else {
+        penalties.join("\n")
+    }
+}
+
 fn menu_button_system(
     mut commands: Commands,
     query: Query<&Interaction, (Changed<Interaction>, With<StartButton>)>,
+    profile: Res<Profile>,
+    play_mode: Res<PlayMode>,
+    ruleset: Res<RulesetKind>,
+    run_length: Res<RunLength>,
     mut next_state: ResMut<NextState<AppState>>,
 ) {
     for interaction in &query {
         if *interaction == Interaction::Pressed {
-            commands.insert_resource(GameState::default());
+            commands.insert_resource(GameState {
+                play_mode: *play_mode,
+                ruleset: *ruleset,
+                final_level: run_length.final_level(),
+                ..GameState::new(profile.selected_stake)
+            });
+            commands.insert_resource(PatternLevels::default());
+            commands.insert_resource(Jokers::default());
+            commands.insert_resource(ActionHistory::default());
             next_state.set(AppState::Playing);
         }
     }
 }
 
+fn stake_button_system(
+    prev_q: Query<&Interaction, (Changed<Interaction>, With<StakePrevButton>)>,
+    next_q: Query<&Interaction, (Changed<Interaction>, With<StakeNextButton>)>,
+    mut profile: ResMut<Profile>,
+) {
+    let mut target = None;
+    for interaction in &prev_q {
+        if *interaction == Interaction::Pressed {
+            target = profile.selected_stake.prev();
+        }
+    }
+    for interaction in &next_q {
+        if *interaction == Interaction::Pressed {
+            target = profile.selected_stake.next();
+        }
+    }
+    if let Some(stake) = target {
+        if profile.select_stake(stake) {
+            profile.save();
+        }
+    }
+}
+
+fn update_stake_display(
+    profile: Res<Profile>,
+    mut label_q: Query<&mut Text, (With<StakeText>, Without<StakePenaltyText>)>,
+    mut penalty_q: Query<&mut Text, (With<StakePenaltyText>, Without<StakeText>)>,
+) {
+    if !profile.is_changed() {
+        return;
+    }
+    if let Ok(mut text) = label_q.single_mut() {
+        text.0 = profile.selected_stake.label().into();
+    }
+    if let Ok(mut text) = penalty_q.single_mut() {
+        text.0 = stake_penalty_summary(profile.selected_stake);
+    }
+}
+
+fn play_mode_button_system(
+    query: Query<&Interaction, (Changed<Interaction>, With<PlayModeButton>)>,
+    mut play_mode: ResMut<PlayMode>,
+    mut text_q: Query<&mut Text, With<PlayModeText>>,
+) {
+    for interaction in &query {
+        if *interaction == Interaction::Pressed {
+            *play_mode = play_mode.toggle();
+            if let Ok(mut text) = text_q.single_mut() {
+                text.0 = play_mode.label().into();
+            }
+        }
+    }
+}
+
+fn ruleset_button_system(
+    query: Query<&Interaction, (Changed<Interaction>, With<RulesetButton>)>,
+    mut ruleset: ResMut<RulesetKind>,
+    mut text_q: Query<&mut Text, With<RulesetText>>,
+) {
+    for interaction in &query {
+        if *interaction == Interaction::Pressed {
+            *ruleset = ruleset.next();
+            if let Ok(mut text) = text_q.single_mut() {
+                text.0 = ruleset.label().into();
+            }
+        }
+    }
+}
+
+fn run_length_button_system(
+    query: Query<&Interaction, (Changed<Interaction>, With<RunLengthButton>)>,
+    mut run_length: ResMut<RunLength>,
+    mut text_q: Query<&mut Text, With<RunLengthText>>,
+) {
+    for interaction in &query {
+        if *interaction == Interaction::Pressed {
+            *run_length = run_length.next();
+            if let Ok(mut text) = text_q.single_mut() {
+                text.0 = run_length.label();
+            }
+        }
+    }
+}
+
 // ===================== GAME UI =====================
 
-fn setup_game_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
+fn setup_game_ui(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    speed: Res<ScoreAnimationSpeed>,
+    sort_mode: Res<SortMode>,
+    jokers: Res<Jokers>,
+) {
     let font = asset_server.load("fonts/pixel.ttf");
 
     commands
@@ -175,7 +520,8 @@ fn setup_game_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
             ))
             .with_children(|joker| {
                 joker.spawn((
-                    Text::new("小丑区 (放置道具, 目前先空白)"),
+                    JokerAreaText,
+                    Text::new(joker_summary(&jokers)),
                     TextFont {
                         font: font.clone(),
                         font_size: 16.0,
@@ -233,15 +579,41 @@ fn setup_game_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
 
                 // Button row
                 bottom
-                    .spawn(Node {
-                        flex_direction: FlexDirection::Row,
-                        column_gap: Val::Px(20.0),
-                        ..default()
-                    })
+                    .spawn((
+                        GameButtonRow,
+                        Node {
+                            flex_direction: FlexDirection::Row,
+                            column_gap: Val::Px(20.0),
+                            ..default()
+                        },
+                    ))
                     .with_children(|btn_row| {
                         spawn_game_button(btn_row, &font, "菜单", MenuButton);
                         spawn_game_button(btn_row, &font, "出牌", PlayButton);
                         spawn_game_button(btn_row, &font, "弃牌", DiscardButton);
+                        spawn_game_button(btn_row, &font, "悔牌", UndoButton);
+                        spawn_game_button(btn_row, &font, "立直", RiichiButton);
+                        spawn_game_button(btn_row, &font, speed.label(), SpeedButton);
+                        spawn_game_button(btn_row, &font, sort_mode.label(), SortButton);
+                    });
+
+                // Sichuan 换三张 / 定缺 row, shown in place of the buttons above
+                bottom
+                    .spawn((
+                        SichuanButtonRow,
+                        Node {
+                            flex_direction: FlexDirection::Row,
+                            column_gap: Val::Px(20.0),
+                            display: Display::None,
+                            ..default()
+                        },
+                    ))
+                    .with_children(|btn_row| {
+                        spawn_game_button(btn_row, &font, "换三张", ExchangeButton);
+                        for suit in [TileSuit::Man, TileSuit::Pin, TileSuit::Sou] {
+                            let label = format!("缺{}", suit.label());
+                            spawn_game_button(btn_row, &font, &label, VoidSuitButton(suit));
+                        }
                     });
             });
         });
@@ -275,6 +647,18 @@ fn build_score_area(parent: &mut ChildSpawnerCommands, font: &Handle<Font>) {
                 TextColor(ACCENT_RED),
             ));
 
+            // Round and seat wind
+            score.spawn((
+                WindText,
+                Text::new(""),
+                TextFont {
+                    font: font.clone(),
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextColor(TEXT_COLOR),
+            ));
+
             // Base score
             score.spawn((
                 BaseScoreText,
@@ -305,7 +689,7 @@ fn build_score_area(parent: &mut ChildSpawnerCommands, font: &Handle<Font>) {
                 Text::new("= 0"),
                 TextFont {
                     font: font.clone(),
-                    font_size: 20.0,
+                    font_size: TOTAL_FONT_SIZE,
                     ..default()
                 },
                 TextColor(Color::srgb(1.0, 0.85, 0.2)),
@@ -326,6 +710,8 @@ fn build_score_area(parent: &mut ChildSpawnerCommands, font: &Handle<Font>) {
             // Plays remaining
             score.spawn((
                 PlaysRemainingText,
+                Pickable::default(),
+                Tooltip::new("出牌: 把选中的牌打到出牌区并从牌山补牌。\n出牌区凑满 14 张或出牌次数用完时计分"),
                 Text::new("出牌: 4"),
                 TextFont {
                     font: font.clone(),
@@ -338,6 +724,8 @@ fn build_score_area(parent: &mut ChildSpawnerCommands, font: &Handle<Font>) {
             // Discard remaining
             score.spawn((
                 DiscardRemainingText,
+                Pickable::default(),
+                Tooltip::new("弃牌: 弃掉选中的牌并从牌山补同样张数,\n不消耗出牌次数"),
                 Text::new("弃牌: 4"),
                 TextFont {
                     font: font.clone(),
@@ -347,9 +735,23 @@ fn build_score_area(parent: &mut ChildSpawnerCommands, font: &Handle<Font>) {
                 TextColor(TEXT_COLOR),
             ));
 
+            // Money
+            score.spawn((
+                MoneyText,
+                Text::new("$0"),
+                TextFont {
+                    font: font.clone(),
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextColor(Color::srgb(1.0, 0.85, 0.2)),
+            ));
+
             // Hand pattern
             score.spawn((
                 HandPatternText,
+                Pickable::default(),
+                Tooltip::default(),
                 Text::new(""),
                 TextFont {
                     font: font.clone(),
@@ -358,6 +760,18 @@ fn build_score_area(parent: &mut ChildSpawnerCommands, font: &Handle<Font>) {
                 },
                 TextColor(Color::srgb(0.5, 1.0, 0.5)),
             ));
+
+            // Preview of the selection
+            score.spawn((
+                PreviewText,
+                Text::new(""),
+                TextFont {
+                    font: font.clone(),
+                    font_size: 12.0,
+                    ..default()
+                },
+                TextColor(Color::srgb(0.7, 0.75, 0.7)),
+            ));
         });
 }
 
@@ -418,6 +832,7 @@ fn build_wall_display(parent: &mut ChildSpawnerCommands, font: &Handle<Font>) {
     parent
         .spawn((
             TileWallDisplay,
+            Pickable::default(),
             Node {
                 width: Val::Px(100.0),
                 flex_direction: FlexDirection::Column,
@@ -451,7 +866,8 @@ fn build_wall_display(parent: &mut ChildSpawnerCommands, font: &Handle<Font>) {
                 },
                 TextColor(Color::srgb(0.7, 0.7, 0.75)),
             ));
-        });
+        })
+        .observe(on_wall_clicked);
 }
 
 fn spawn_game_button(parent: &mut ChildSpawnerCommands, font: &Handle<Font>, label: &str, marker: impl Component) {
@@ -490,6 +906,8 @@ fn game_button_system(
     play_q: Query<&Interaction, (Changed<Interaction>, With<PlayButton>)>,
     discard_q: Query<&Interaction, (Changed<Interaction>, With<DiscardButton>)>,
     menu_q: Query<&Interaction, (Changed<Interaction>, With<MenuButton>)>,
+    speed_q: Query<&Interaction, (Changed<Interaction>, With<SpeedButton>)>,
+    mut speed: ResMut<ScoreAnimationSpeed>,
     mut next_state: ResMut<NextState<AppState>>,
 ) {
     for interaction in &play_q {
@@ -507,30 +925,412 @@ fn game_button_system(
             next_state.set(AppState::Menu);
         }
     }
+    for interaction in &speed_q {
+        if *interaction == Interaction::Pressed {
+            *speed = speed.next();
+        }
+    }
 }
 
-// ===================== DISPLAY UPDATES =====================
+fn riichi_button_system(
+    mut commands: Commands,
+    query: Query<&Interaction, (Changed<Interaction>, With<RiichiButton>)>,
+) {
+    for interaction in &query {
+        if *interaction == Interaction::Pressed {
+            commands.trigger(DeclareRiichiEvent);
+        }
+    }
+}
 
-fn update_score_display(
-    game_state: Option<Res<GameState>>,
-    mut base_q: Query<&mut Text, (With<BaseScoreText>, Without<MultiplierText>, Without<TotalScoreText>, Without<TargetScoreText>, Without<PlaysRemainingText>, Without<DiscardRemainingText>, Without<SubRoundText>)>,
-    mut mult_q: Query<&mut Text, (With<MultiplierText>, Without<BaseScoreText>, Without<TotalScoreText>, Without<TargetScoreText>, Without<PlaysRemainingText>, Without<DiscardRemainingText>, Without<SubRoundText>)>,
-    mut total_q: Query<&mut Text, (With<TotalScoreText>, Without<BaseScoreText>, Without<MultiplierText>, Without<TargetScoreText>, Without<PlaysRemainingText>, Without<DiscardRemainingText>, Without<SubRoundText>)>,
-    mut target_q: Query<&mut Text, (With<TargetScoreText>, Without<BaseScoreText>, Without<MultiplierText>, Without<TotalScoreText>, Without<PlaysRemainingText>, Without<DiscardRemainingText>, Without<SubRoundText>)>,
-    mut plays_q: Query<&mut Text, (With<PlaysRemainingText>, Without<BaseScoreText>, Without<MultiplierText>, Without<TotalScoreText>, Without<TargetScoreText>, Without<DiscardRemainingText>, Without<SubRoundText>)>,
-    mut discard_q: Query<&mut Text, (With<DiscardRemainingText>, Without<BaseScoreText>, Without<MultiplierText>, Without<TotalScoreText>, Without<TargetScoreText>, Without<PlaysRemainingText>, Without<SubRoundText>)>,
-    mut sub_round_q: Query<&mut Text, (With<SubRoundText>, Without<BaseScoreText>, Without<MultiplierText>, Without<TotalScoreText>, Without<TargetScoreText>, Without<PlaysRemainingText>, Without<DiscardRemainingText>)>,
+fn undo_button_system(
+    mut commands: Commands,
+    query: Query<&Interaction, (Changed<Interaction>, With<UndoButton>)>,
 ) {
-    let Some(gs) = game_state else { return };
-    if !gs.is_changed() {
-        return;
+    for interaction in &query {
+        if *interaction == Interaction::Pressed {
+            commands.trigger(UndoEvent);
+        }
     }
+}
 
-    if let Ok(mut text) = base_q.single_mut() {
-        text.0 = format!("底注: {}", gs.base_ante);
+fn sort_button_system(
+    query: Query<&Interaction, (Changed<Interaction>, With<SortButton>)>,
+    mut sort_mode: ResMut<SortMode>,
+) {
+    for interaction in &query {
+        if *interaction == Interaction::Pressed {
+            *sort_mode = sort_mode.next();
+        }
     }
-    if let Ok(mut text) = mult_q.single_mut() {
-        text.0 = format!("× 倍率: {}", gs.multiplier);
+}
+
+fn sichuan_button_system(
+    mut commands: Commands,
+    exchange_q: Query<&Interaction, (Changed<Interaction>, With<ExchangeButton>)>,
+    void_q: Query<(&Interaction, &VoidSuitButton), Changed<Interaction>>,
+) {
+    for interaction in &exchange_q {
+        if *interaction == Interaction::Pressed {
+            commands.trigger(ExchangeTilesEvent);
+        }
+    }
+    for (interaction, button) in &void_q {
+        if *interaction == Interaction::Pressed {
+            commands.trigger(DeclareVoidEvent { suit: button.0 });
+        }
+    }
+}
+
+// ===================== DISPLAY UPDATES =====================
+
+/// Swap the play buttons for the 换三张 / 定缺 row until a Sichuan blind's void is declared
+fn update_button_rows(
+    game_state: Res<GameState>,
+    mut game_row_q: Query<&mut Node, (With<GameButtonRow>, Without<SichuanButtonRow>)>,
+    mut sichuan_row_q: Query<&mut Node, With<SichuanButtonRow>>,
+) {
+    if !game_state.is_changed() {
+        return;
+    }
+    let awaiting = game_state.awaiting_void();
+    let display = |shown: bool| if shown { Display::Flex } else { Display::None };
+    if let Ok(mut node) = game_row_q.single_mut() {
+        node.display = display(!awaiting);
+    }
+    if let Ok(mut node) = sichuan_row_q.single_mut() {
+        node.display = display(awaiting);
+    }
+}
+
+/// Show when riichi is in effect on its button
+fn update_riichi_button(
+    game_state: Res<GameState>,
+    button_q: Query<&Children, With<RiichiButton>>,
+    mut text_q: Query<&mut Text>,
+) {
+    if !game_state.is_changed() {
+        return;
+    }
+    let label = if game_state.riichi.is_some() { "立直中" } else { "立直" };
+    for children in &button_q {
+        for child in children.iter() {
+            if let Ok(mut text) = text_q.get_mut(child) {
+                text.0 = label.into();
+            }
+        }
+    }
+}
+
+fn update_speed_button(
+    speed: Res<ScoreAnimationSpeed>,
+    button_q: Query<&Children, With<SpeedButton>>,
+    mut text_q: Query<&mut Text>,
+) {
+    if !speed.is_changed() {
+        return;
+    }
+    for children in &button_q {
+        for child in children.iter() {
+            if let Ok(mut text) = text_q.get_mut(child) {
+                text.0 = speed.label().into();
+            }
+        }
+    }
+}
+
+/// Show how many undos are left, dimmed when there's nothing to take back
+fn update_undo_button(
+    history: Res<ActionHistory>,
+    button_q: Query<(&Children, Ref<UndoButton>)>,
+    mut text_q: Query<(&mut Text, &mut TextColor)>,
+) {
+    let color = if history.can_undo() { TEXT_COLOR } else { TEXT_DIM };
+    for (children, button) in &button_q {
+        if !history.is_changed() && !button.is_added() {
+            continue;
+        }
+        for child in children.iter() {
+            if let Ok((mut text, mut text_color)) = text_q.get_mut(child) {
+                text.0 = format!("悔牌 {}", history.undos_left);
+                text_color.0 = color;
+            }
+        }
+    }
+}
+
+fn update_sort_button(
+    sort_mode: Res<SortMode>,
+    button_q: Query<&Children, With<SortButton>>,
+    mut text_q: Query<&mut Text>,
+) {
+    if !sort_mode.is_changed() {
+        return;
+    }
+    for children in &button_q {
+        for child in children.iter() {
+            if let Ok(mut text) = text_q.get_mut(child) {
+                text.0 = sort_mode.label().into();
+            }
+        }
+    }
+}
+
+/// Observer: show the final breakdown and slam the total in
+fn on_score_calculated(
+    trigger: On<ScoreCalculatedEvent>,
+    mut pattern_q: Query<(&mut Text, &mut Tooltip), With<HandPatternText>>,
+    mut total_q: Query<&mut TextFont, With<TotalScoreText>>,
+) {
+    let event = trigger.event();
+    if let Ok((mut text, mut tooltip)) = pattern_q.single_mut() {
+        text.0 = format!(
+            "{} ({}×{}={})",
+            event.pattern_name, event.base, event.multiplier, event.total
+        );
+        let hand = &event.hand;
+        tooltip.0 = format!(
+            "{} Lv.{}: {}\n牌型起始 {}×{}, 牌和加成算完 {}×{}",
+            hand.pattern.label(),
+            hand.level,
+            hand.pattern.description(),
+            hand.base,
+            hand.multiplier,
+            event.base,
+            event.multiplier,
+        );
+    }
+    if let Ok(mut font) = total_q.single_mut() {
+        font.font_size = TOTAL_SLAM_FONT_SIZE;
+    }
+}
+
+/// Observer: explain why a play didn't go through
+fn on_play_rejected(trigger: On<PlayRejectedEvent>, mut pattern_q: Query<&mut Text, With<HandPatternText>>) {
+    if let Ok(mut text) = pattern_q.single_mut() {
+        text.0 = trigger.event().reason.into();
+    }
+}
+
+/// Observer: name a right-clicked tile, its enhancement and where it sits
+fn on_inspect_tile(
+    trigger: On<InspectTileEvent>,
+    tile_q: Query<(&Tile, &TileLocation, Option<&TileEnhancement>)>,
+    mut pattern_q: Query<&mut Text, With<HandPatternText>>,
+) {
+    let Ok((tile, location, enhancement)) = tile_q.get(trigger.event().tile_entity) else {
+        return;
+    };
+    if let Ok(mut text) = pattern_q.single_mut() {
+        let enhancement = enhancement.map(|e| format!(" · {}", e.label())).unwrap_or_default();
+        text.0 = format!("{}{} · {}", tile.id.label(), enhancement, location.label());
+    }
+}
+
+/// Clicking the wall counts what's left in it by suit
+fn on_wall_clicked(
+    mut click: On<Pointer<Click>>,
+    wall: Res<TileWall>,
+    tile_q: Query<&Tile>,
+    mut pattern_q: Query<&mut Text, With<HandPatternText>>,
+) {
+    click.propagate(false);
+    let mut counts: Vec<(TileSuit, usize)> = Vec::new();
+    for tile in tile_q.iter_many(&wall.tiles) {
+        match counts.iter_mut().find(|(suit, _)| *suit == tile.id.suit) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((tile.id.suit, 1)),
+        }
+    }
+    counts.sort();
+    if let Ok(mut text) = pattern_q.single_mut() {
+        let summary: Vec<String> = counts
+            .iter()
+            .map(|(suit, count)| format!("{}{}", suit.label(), count))
+            .collect();
+        text.0 = format!("牌山: {}", summary.join(" "));
+    }
+}
+
+fn spawn_tooltip(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands
+        .spawn((
+            TooltipBox,
+            Node {
+                display: Display::None,
+                position_type: PositionType::Absolute,
+                max_width: Val::Px(280.0),
+                border: UiRect::all(Val::Px(2.0)),
+                padding: UiRect::all(Val::Px(8.0)),
+                ..default()
+            },
+            BorderColor::all(BORDER_COLOR),
+            BackgroundColor(BG_DARK),
+            GlobalZIndex(100),
+        ))
+        .with_children(|tooltip| {
+            tooltip.spawn((
+                TooltipText,
+                Text::new(""),
+                TextFont {
+                    font: asset_server.load("fonts/pixel.ttf"),
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextColor(TEXT_COLOR),
+            ));
+        });
+}
+
+/// Observer: remember the entity the pointer went over, if it has something to say
+fn on_tooltip_over(
+    over: On<Pointer<Over>>,
+    mut hovered: ResMut<HoveredTooltip>,
+    tooltip_q: Query<(), With<Tooltip>>,
+    tile_q: Query<(), With<Tile>>,
+) {
+    let entity = over.original_event_target();
+    if tooltip_q.contains(entity) || tile_q.contains(entity) {
+        hovered.0 = Some(entity);
+    }
+}
+
+fn on_tooltip_out(out: On<Pointer<Out>>, mut hovered: ResMut<HoveredTooltip>) {
+    if hovered.0 == Some(out.original_event_target()) {
+        hovered.0 = None;
+    }
+}
+
+/// Tile name, enhancement and copies still in the wall
+fn tile_tooltip(
+    tile: &Tile,
+    enhancement: Option<&TileEnhancement>,
+    wall: impl IntoIterator<Item = TileId>,
+) -> String {
+    let copies_left = wall.into_iter().filter(|&id| id == tile.id).count();
+    let mut lines = vec![tile.id.label()];
+    if let Some(enhancement) = enhancement {
+        lines.push(format!("{}: {}", enhancement.label(), enhancement.description()));
+    }
+    lines.push(format!("牌山剩余 {} 张", copies_left));
+    lines.join("\n")
+}
+
+fn update_tooltip_text(
+    hovered: Res<HoveredTooltip>,
+    wall: Res<TileWall>,
+    tooltip_q: Query<&Tooltip>,
+    tile_q: Query<(&Tile, Option<&TileEnhancement>)>,
+    mut text_q: Query<&mut Text, With<TooltipText>>,
+) {
+    let Ok(mut text) = text_q.single_mut() else { return };
+    let content = match hovered.0 {
+        Some(entity) => match (tooltip_q.get(entity), tile_q.get(entity)) {
+            (Ok(tooltip), _) => tooltip.0.clone(),
+            (_, Ok((tile, enhancement))) => {
+                let wall_ids = tile_q.iter_many(&wall.tiles).map(|(t, _)| t.id);
+                tile_tooltip(tile, enhancement, wall_ids)
+            }
+            _ => String::new(),
+        },
+        None => String::new(),
+    };
+    if text.0 != content {
+        text.0 = content;
+    }
+}
+
+/// Follow the pointer; hidden while there's nothing to show
+fn position_tooltip(
+    windows: Query<&Window>,
+    text_q: Query<&Text, With<TooltipText>>,
+    mut box_q: Query<&mut Node, With<TooltipBox>>,
+) {
+    let Ok(mut node) = box_q.single_mut() else { return };
+    let cursor = windows.single().ok().and_then(|window| window.cursor_position());
+    let visible = text_q.single().is_ok_and(|text| !text.0.is_empty());
+    match cursor.filter(|_| visible) {
+        Some(cursor) => {
+            let pos = cursor + TOOLTIP_OFFSET;
+            node.display = Display::Flex;
+            node.left = Val::Px(pos.x);
+            node.top = Val::Px(pos.y);
+        }
+        None => node.display = Display::None,
+    }
+}
+
+fn shanten_label(shanten: i32) -> String {
+    match shanten {
+        ..0 => "和了".into(),
+        0 => "听牌".into(),
+        n => format!("{n}向听"),
+    }
+}
+
+/// Projected pattern, score and 向听 change of the selection, with a
+/// warning when it would overfill the board
+fn update_preview_text(
+    selection: Res<SelectionPreview>,
+    mut query: Query<&mut Text, With<PreviewText>>,
+) {
+    if !selection.is_changed() {
+        return;
+    }
+    let Ok(mut text) = query.single_mut() else { return };
+    let Some(preview) = &selection.preview else {
+        text.0.clear();
+        return;
+    };
+    let mut lines = match preview.rejected {
+        Some(reason) => vec![format!("预览: {reason}")],
+        None => vec![
+            format!("预览: {}", preview.heading),
+            format!("{}×{}={}", preview.chips, preview.mult, preview.total),
+        ],
+    };
+    let (before, after) = preview.shanten;
+    lines.push(format!("{} → {}", shanten_label(before), shanten_label(after)));
+    if preview.over_limit {
+        lines.push("超过 14 张!".into());
+    }
+    text.0 = lines.join("\n");
+}
+
+/// Shrink the total back to its resting size after a slam
+fn ease_total_score_text(time: Res<Time>, mut query: Query<&mut TextFont, With<TotalScoreText>>) {
+    for mut font in &mut query {
+        if font.font_size > TOTAL_FONT_SIZE {
+            let eased = font.font_size.lerp(TOTAL_FONT_SIZE, (time.delta_secs() * 6.0).min(1.0));
+            font.font_size = if eased - TOTAL_FONT_SIZE < 0.1 {
+                TOTAL_FONT_SIZE
+            } else {
+                eased
+            };
+        }
+    }
+}
+
+fn update_score_display(
+    game_state: Option<Res<GameState>>,
+    mut base_q: Query<&mut Text, (With<BaseScoreText>, Without<MultiplierText>, Without<TotalScoreText>, Without<TargetScoreText>, Without<PlaysRemainingText>, Without<DiscardRemainingText>, Without<SubRoundText>)>,
+    mut mult_q: Query<&mut Text, (With<MultiplierText>, Without<BaseScoreText>, Without<TotalScoreText>, Without<TargetScoreText>, Without<PlaysRemainingText>, Without<DiscardRemainingText>, Without<SubRoundText>)>,
+    mut total_q: Query<&mut Text, (With<TotalScoreText>, Without<BaseScoreText>, Without<MultiplierText>, Without<TargetScoreText>, Without<PlaysRemainingText>, Without<DiscardRemainingText>, Without<SubRoundText>)>,
+    mut target_q: Query<&mut Text, (With<TargetScoreText>, Without<BaseScoreText>, Without<MultiplierText>, Without<TotalScoreText>, Without<PlaysRemainingText>, Without<DiscardRemainingText>, Without<SubRoundText>)>,
+    mut plays_q: Query<&mut Text, (With<PlaysRemainingText>, Without<BaseScoreText>, Without<MultiplierText>, Without<TotalScoreText>, Without<TargetScoreText>, Without<DiscardRemainingText>, Without<SubRoundText>)>,
+    mut discard_q: Query<&mut Text, (With<DiscardRemainingText>, Without<BaseScoreText>, Without<MultiplierText>, Without<TotalScoreText>, Without<TargetScoreText>, Without<PlaysRemainingText>, Without<SubRoundText>)>,
+    mut sub_round_q: Query<&mut Text, (With<SubRoundText>, Without<BaseScoreText>, Without<MultiplierText>, Without<TotalScoreText>, Without<TargetScoreText>, Without<PlaysRemainingText>, Without<DiscardRemainingText>)>,
+) {
+    let Some(gs) = game_state else { return };
+    if !gs.is_changed() {
+        return;
+    }
+
+    if let Ok(mut text) = base_q.single_mut() {
+        text.0 = format!("底注: {}", gs.base_ante);
+    }
+    if let Ok(mut text) = mult_q.single_mut() {
+        text.0 = format!("× 倍率: {}", gs.multiplier);
     }
     if let Ok(mut text) = total_q.single_mut() {
         text.0 = format!("= {}", gs.current_score);
@@ -545,10 +1345,70 @@ fn update_score_display(
         text.0 = format!("弃牌: {}", gs.discards_remaining);
     }
     if let Ok(mut text) = sub_round_q.single_mut() {
-        text.0 = format!("Lv.{} {}", gs.level, gs.sub_round.label());
+        text.0 = if gs.endless {
+            format!("无尽 Lv.{} {} {}", gs.level, gs.sub_round.label(), gs.stake.label())
+        } else {
+            format!(
+                "Lv.{}/{} {} {}",
+                gs.level,
+                gs.final_level,
+                gs.sub_round.label(),
+                gs.stake.label()
+            )
+        };
+        if let Some(modifier) = gs.boss_modifier {
+            text.0 += &format!("\n{}", modifier.label());
+        }
     }
 }
 
+fn joker_summary(jokers: &Jokers) -> String {
+    if jokers.slots.is_empty() {
+        return "小丑区 (空)".into();
+    }
+    let labels: Vec<String> = jokers.slots.iter().map(|joker| joker.label()).collect();
+    labels.join("    ")
+}
+
+fn update_joker_display(jokers: Res<Jokers>, mut query: Query<&mut Text, With<JokerAreaText>>) {
+    if !jokers.is_changed() {
+        return;
+    }
+    if let Ok(mut text) = query.single_mut() {
+        text.0 = joker_summary(&jokers);
+    }
+}
+
+fn update_money_display(
+    game_state: Option<Res<GameState>>,
+    mut query: Query<&mut Text, With<MoneyText>>,
+) {
+    let Some(gs) = game_state else { return };
+    if !gs.is_changed() {
+        return;
+    }
+    if let Ok(mut text) = query.single_mut() {
+        text.0 = format!("${}", gs.money);
+    }
+}
+
+fn update_wind_display(
+    game_state: Option<Res<GameState>>,
+    mut query: Query<&mut Text, With<WindText>>,
+) {
+    let Some(gs) = game_state else { return };
+    if !gs.is_changed() {
+        return;
+    }
+    if let Ok(mut text) = query.single_mut() {
+        text.0 = format!("场风 {}  自风 {}", gs.round_wind().label(), gs.seat_wind().label());
+        if let Some(suit) = gs.void_suit {
+            text.0 += &format!("  缺{}", suit.label());
+        }
+    }
+}
+
+
 fn update_wall_count(
     wall: Res<TileWall>,
     mut query: Query<&mut Text, With<WallCountText>>,
@@ -561,6 +1421,270 @@ fn update_wall_count(
     }
 }
 
+// ===================== SHOP =====================
+
+fn setup_shop_ui(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    offers: Res<ShopOffers>,
+    levels: Res<PatternLevels>,
+    jokers: Res<Jokers>,
+    history: Res<ActionHistory>,
+) {
+    let font = asset_server.load("fonts/pixel.ttf");
+
+    commands
+        .spawn((
+            ShopRoot,
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+        ))
+        .with_children(|overlay| {
+            overlay
+                .spawn((
+                    Node {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        border: UiRect::all(Val::Px(2.0)),
+                        padding: UiRect::all(Val::Px(20.0)),
+                        row_gap: Val::Px(16.0),
+                        ..default()
+                    },
+                    BorderColor::all(BORDER_COLOR),
+                    BackgroundColor(BG_PANEL),
+                ))
+                .with_children(|panel| {
+                    panel.spawn((
+                        Text::new("商店"),
+                        TextFont {
+                            font: font.clone(),
+                            font_size: 28.0,
+                            ..default()
+                        },
+                        TextColor(ACCENT_RED),
+                    ));
+
+                    panel.spawn((
+                        ShopMoneyText,
+                        Text::new(""),
+                        TextFont {
+                            font: font.clone(),
+                            font_size: 16.0,
+                            ..default()
+                        },
+                        TextColor(Color::srgb(1.0, 0.85, 0.2)),
+                    ));
+
+                    // Pattern cards
+                    panel
+                        .spawn(Node {
+                            flex_direction: FlexDirection::Row,
+                            column_gap: Val::Px(16.0),
+                            ..default()
+                        })
+                        .with_children(|cards| {
+                            for (index, card) in offers.cards.iter().enumerate() {
+                                let level = levels.level(card.pattern);
+                                let label = format!(
+                                    "{}\nLv.{} → Lv.{}\n${}",
+                                    card.pattern.label(),
+                                    level,
+                                    level + 1,
+                                    PATTERN_CARD_PRICE
+                                );
+                                spawn_shop_card(cards, &font, label, PatternCardButton { index });
+                            }
+                            let label = format!(
+                                "悔牌 +1\n每关 {} → {} 次\n${}",
+                                history.limit,
+                                history.limit + 1,
+                                UNDO_PERK_PRICE
+                            );
+                            spawn_shop_card(cards, &font, label, UndoPerkButton);
+                        });
+
+                    // Jokers on offer
+                    panel
+                        .spawn(Node {
+                            flex_direction: FlexDirection::Row,
+                            column_gap: Val::Px(16.0),
+                            ..default()
+                        })
+                        .with_children(|cards| {
+                            for (index, offer) in offers.jokers.iter().enumerate() {
+                                let label = format!(
+                                    "{}\n{}\n${}",
+                                    offer.joker.label(),
+                                    offer.joker.kind.description(),
+                                    JOKER_PRICE
+                                );
+                                spawn_shop_card(cards, &font, label, JokerCardButton { index });
+                            }
+                        });
+
+                    // Owned jokers, sold by clicking
+                    panel
+                        .spawn(Node {
+                            flex_direction: FlexDirection::Row,
+                            column_gap: Val::Px(8.0),
+                            ..default()
+                        })
+                        .with_children(|slots| {
+                            for slot in 0..JOKER_SLOTS {
+                                spawn_shop_card(slots, &font, sell_label(&jokers, slot), SellJokerButton { slot });
+                            }
+                        });
+
+                    spawn_game_button(panel, &font, "下一关", LeaveShopButton);
+                });
+        });
+}
+
+fn spawn_shop_card(parent: &mut ChildSpawnerCommands, font: &Handle<Font>, label: String, marker: impl Component) {
+    parent
+        .spawn((
+            marker,
+            Button,
+            Node {
+                width: Val::Px(140.0),
+                height: Val::Px(100.0),
+                border: UiRect::all(Val::Px(2.0)),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            BorderColor::all(BORDER_COLOR),
+            BackgroundColor(BG_BUTTON),
+        ))
+        .with_children(|btn| {
+            btn.spawn((
+                Text::new(label),
+                TextFont {
+                    font: font.clone(),
+                    font_size: 16.0,
+                    ..default()
+                },
+                TextColor(TEXT_COLOR),
+                TextLayout::new_with_justify(Justify::Center),
+            ));
+        });
+}
+
+fn sell_label(jokers: &Jokers, slot: usize) -> String {
+    match jokers.slots.get(slot) {
+        Some(joker) if joker.eternal => format!("{}\n不可出售", joker.kind.label()),
+        Some(joker) => format!("{}\n出售 ${}", joker.kind.label(), JOKER_SELL_PRICE),
+        None => "空位".into(),
+    }
+}
+
+fn shop_button_system(
+    mut commands: Commands,
+    card_q: Query<(&Interaction, &PatternCardButton), Changed<Interaction>>,
+    joker_q: Query<(&Interaction, &JokerCardButton), Changed<Interaction>>,
+    sell_q: Query<(&Interaction, &SellJokerButton), Changed<Interaction>>,
+    perk_q: Query<&Interaction, (Changed<Interaction>, With<UndoPerkButton>)>,
+    leave_q: Query<&Interaction, (Changed<Interaction>, With<LeaveShopButton>)>,
+) {
+    for (interaction, card) in &card_q {
+        if *interaction == Interaction::Pressed {
+            commands.trigger(BuyPatternCardEvent { index: card.index });
+        }
+    }
+    for (interaction, card) in &joker_q {
+        if *interaction == Interaction::Pressed {
+            commands.trigger(BuyJokerEvent { index: card.index });
+        }
+    }
+    for (interaction, button) in &sell_q {
+        if *interaction == Interaction::Pressed {
+            commands.trigger(SellJokerEvent { slot: button.slot });
+        }
+    }
+    for interaction in &perk_q {
+        if *interaction == Interaction::Pressed {
+            commands.trigger(BuyUndoPerkEvent);
+        }
+    }
+    for interaction in &leave_q {
+        if *interaction == Interaction::Pressed {
+            commands.trigger(LeaveShopEvent);
+        }
+    }
+}
+
+fn update_shop_display(
+    offers: Res<ShopOffers>,
+    game_state: Res<GameState>,
+    card_q: Query<(&PatternCardButton, &Children)>,
+    perk_q: Query<&Children, With<UndoPerkButton>>,
+    mut money_q: Query<&mut Text, With<ShopMoneyText>>,
+    mut text_q: Query<&mut Text, Without<ShopMoneyText>>,
+) {
+    if !offers.is_changed() && !game_state.is_changed() {
+        return;
+    }
+    if let Ok(mut text) = money_q.single_mut() {
+        text.0 = format!("持有: ${}", game_state.money);
+    }
+    if offers.undo_perk_sold {
+        for child in perk_q.iter().flat_map(|children| children.iter()) {
+            if let Ok(mut text) = text_q.get_mut(child) {
+                text.0 = "悔牌 +1\n已购买".into();
+            }
+        }
+    }
+    for (card, children) in &card_q {
+        let Some(offer) = offers.cards.get(card.index) else {
+            continue;
+        };
+        if !offer.sold {
+            continue;
+        }
+        for child in children.iter() {
+            if let Ok(mut text) = text_q.get_mut(child) {
+                text.0 = format!("{}\n已购买", offer.pattern.label());
+            }
+        }
+    }
+}
+
+fn update_shop_jokers(
+    offers: Res<ShopOffers>,
+    jokers: Res<Jokers>,
+    offer_q: Query<(&JokerCardButton, &Children)>,
+    slot_q: Query<(&SellJokerButton, &Children)>,
+    mut text_q: Query<&mut Text>,
+) {
+    if !offers.is_changed() && !jokers.is_changed() {
+        return;
+    }
+    for (card, children) in &offer_q {
+        let Some(offer) = offers.jokers.get(card.index).filter(|offer| offer.sold) else {
+            continue;
+        };
+        for child in children.iter() {
+            if let Ok(mut text) = text_q.get_mut(child) {
+                text.0 = format!("{}\n已购买", offer.joker.kind.label());
+            }
+        }
+    }
+    for (button, children) in &slot_q {
+        for child in children.iter() {
+            if let Ok(mut text) = text_q.get_mut(child) {
+                text.0 = sell_label(&jokers, button.slot);
+            }
+        }
+    }
+}
+
 // ===================== GAME OVER =====================
 
 fn setup_gameover_ui(
@@ -569,7 +1693,12 @@ fn setup_gameover_ui(
     game_state: Option<Res<GameState>>,
 ) {
     let font = asset_server.load("fonts/pixel.ttf");
-    let level = game_state.map(|gs| gs.level).unwrap_or(1);
+    let (level, endless) = game_state.map(|gs| (gs.level, gs.endless)).unwrap_or((1, false));
+    let reached = if endless {
+        format!("到达关卡: {} (无尽)", level)
+    } else {
+        format!("到达关卡: {}", level)
+    };
 
     commands
         .spawn((
@@ -597,7 +1726,7 @@ fn setup_gameover_ui(
             ));
 
             parent.spawn((
-                Text::new(format!("到达关卡: {}", level)),
+                Text::new(reached),
                 TextFont {
                     font: font.clone(),
                     font_size: 24.0,
@@ -648,6 +1777,116 @@ fn gameover_button_system(
     }
 }
 
+// ===================== VICTORY =====================
+
+fn setup_victory_ui(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    game_state: Option<Res<GameState>>,
+) {
+    let font = asset_server.load("fonts/pixel.ttf");
+    let summary = game_state
+        .map(|gs| format!("{} 通关 {} 关", gs.stake.label(), gs.final_level))
+        .unwrap_or_default();
+
+    commands
+        .spawn((
+            VictoryRoot,
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                row_gap: Val::Px(30.0),
+                ..default()
+            },
+            BackgroundColor(BG_DARK),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new("胜利!"),
+                TextFont {
+                    font: font.clone(),
+                    font_size: 48.0,
+                    ..default()
+                },
+                TextColor(Color::srgb(1.0, 0.85, 0.2)),
+            ));
+
+            parent.spawn((
+                Text::new(summary),
+                TextFont {
+                    font: font.clone(),
+                    font_size: 24.0,
+                    ..default()
+                },
+                TextColor(TEXT_COLOR),
+            ));
+
+            parent
+                .spawn(Node {
+                    flex_direction: FlexDirection::Row,
+                    column_gap: Val::Px(20.0),
+                    ..default()
+                })
+                .with_children(|row| {
+                    spawn_screen_button(row, &font, "无尽模式", EndlessButton);
+                    spawn_screen_button(row, &font, "返回菜单", StartButton);
+                });
+        });
+}
+
+fn spawn_screen_button(parent: &mut ChildSpawnerCommands, font: &Handle<Font>, label: &str, marker: impl Component) {
+    parent
+        .spawn((
+            marker,
+            Button,
+            Node {
+                width: Val::Px(200.0),
+                height: Val::Px(60.0),
+                border: UiRect::all(Val::Px(3.0)),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            BorderColor::all(BORDER_COLOR),
+            BackgroundColor(BG_BUTTON),
+        ))
+        .with_children(|btn| {
+            btn.spawn((
+                Text::new(label),
+                TextFont {
+                    font: font.clone(),
+                    font_size: 24.0,
+                    ..default()
+                },
+                TextColor(TEXT_COLOR),
+            ));
+        });
+}
+
+fn victory_button_system(
+    mut commands: Commands,
+    endless_q: Query<&Interaction, (Changed<Interaction>, With<EndlessButton>)>,
+    menu_q: Query<&Interaction, (Changed<Interaction>, With<StartButton>)>,
+    mut game_state: ResMut<GameState>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    for interaction in &endless_q {
+        if *interaction == Interaction::Pressed {
+            game_state.enter_endless();
+            next_state.set(AppState::Playing);
+        }
+    }
+    for interaction in &menu_q {
+        if *interaction == Interaction::Pressed {
+            commands.insert_resource(GameState::default());
+            next_state.set(AppState::Menu);
+        }
+    }
+}
+
 // ===================== BUTTON HOVER =====================
 
 fn button_hover_system(
@@ -661,3 +1900,22 @@ fn button_hover_system(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tile_tooltip() {
+        let id = |suit, value| TileId { suit, value };
+        let tile = Tile { id: id(TileSuit::Pin, 5), copy_index: 0 };
+        let wall = [id(TileSuit::Pin, 5), id(TileSuit::Man, 5), id(TileSuit::Pin, 5)];
+
+        let text = tile_tooltip(&tile, None, wall);
+        assert_eq!(text, "5筒\n牌山剩余 2 张");
+
+        let text = tile_tooltip(&tile, Some(&TileEnhancement::Bonus), []);
+        assert!(text.starts_with("5筒\n金牌: "));
+        assert!(text.ends_with("牌山剩余 0 张"));
+    }
+}
@@ -1,4 +1,14 @@
+use std::collections::HashMap;
+use std::fs;
+
 use bevy::prelude::*;
+use rand::seq::SliceRandom;
+use rand::thread_rng;
+
+use crate::components::tile::{TileId, TileSuit};
+use crate::plugins::ruleset::RulesetKind;
+use crate::plugins::scoring::{HandPattern, JokerKind, Meld, MeldKind, ScoreModifier};
+use crate::score::Score;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SubRound {
@@ -31,66 +41,370 @@ impl SubRound {
             SubRound::Boss => None,
         }
     }
+
+    /// Money awarded for clearing this blind
+    pub fn reward(&self) -> u32 {
+        match self {
+            SubRound::SmallBlind => 3,
+            SubRound::BigBlind => 4,
+            SubRound::Boss => 5,
+        }
+    }
+}
+
+/// Difficulty ladder chosen on the menu.
+/// Each stake keeps every penalty of the stakes below it and adds one more.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum Stake {
+    #[default]
+    White,
+    Red,
+    Green,
+    Black,
+    Blue,
+    Gold,
+}
+
+impl Stake {
+    pub const ALL: [Stake; 6] = [
+        Stake::White,
+        Stake::Red,
+        Stake::Green,
+        Stake::Black,
+        Stake::Blue,
+        Stake::Gold,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Stake::White => "白注",
+            Stake::Red => "红注",
+            Stake::Green => "绿注",
+            Stake::Black => "黑注",
+            Stake::Blue => "蓝注",
+            Stake::Gold => "金注",
+        }
+    }
+
+    pub fn index(&self) -> usize {
+        *self as usize
+    }
+
+    pub fn from_index(index: usize) -> Option<Stake> {
+        Self::ALL.get(index).copied()
+    }
+
+    pub fn next(&self) -> Option<Stake> {
+        Self::from_index(self.index() + 1)
+    }
+
+    pub fn prev(&self) -> Option<Stake> {
+        self.index().checked_sub(1).and_then(Self::from_index)
+    }
+
+    /// 红注: small blind pays no reward
+    pub fn no_small_blind_reward(&self) -> bool {
+        *self >= Stake::Red
+    }
+
+    /// 绿注: targets grow with the triangular number of the level instead of linearly
+    pub fn steep_targets(&self) -> bool {
+        *self >= Stake::Green
+    }
+
+    /// 黑注: jokers offered may be eternal (cannot be sold or destroyed)
+    pub fn eternal_jokers(&self) -> bool {
+        *self >= Stake::Black
+    }
+
+    /// 蓝注: one fewer discard per blind
+    pub fn discard_penalty(&self) -> u32 {
+        if *self >= Stake::Blue {
+            1
+        } else {
+            0
+        }
+    }
+
+    /// 金注: big blinds also carry a boss modifier
+    pub fn boss_modifier_on_big_blind(&self) -> bool {
+        *self >= Stake::Gold
+    }
+
+    /// Human-readable list of every penalty active at this stake
+    pub fn penalties(&self) -> Vec<&'static str> {
+        let mut lines = Vec::new();
+        if self.no_small_blind_reward() {
+            lines.push("小盲注无奖励");
+        }
+        if self.steep_targets() {
+            lines.push("目标分增长更快");
+        }
+        if self.eternal_jokers() {
+            lines.push("小丑可能为永恒");
+        }
+        if self.discard_penalty() > 0 {
+            lines.push("弃牌 -1");
+        }
+        if self.boss_modifier_on_big_blind() {
+            lines.push("大盲注附带 Boss 效果");
+        }
+        lines
+    }
+}
+
+/// Extra rule a boss blind is played under; at 金注 big blinds get one too
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BossModifier {
+    /// One fewer play
+    FewerPlays,
+    /// No discards at all
+    NoDiscards,
+    /// Tiles of this suit don't score
+    DebuffedSuit(TileSuit),
+}
+
+impl BossModifier {
+    pub fn roll() -> Self {
+        let mut rng = thread_rng();
+        let suits = [TileSuit::Man, TileSuit::Pin, TileSuit::Sou];
+        let suit = *suits.choose(&mut rng).unwrap();
+        let modifiers = [
+            BossModifier::FewerPlays,
+            BossModifier::NoDiscards,
+            BossModifier::DebuffedSuit(suit),
+        ];
+        *modifiers.choose(&mut rng).unwrap()
+    }
+
+    pub fn label(&self) -> String {
+        match self {
+            BossModifier::FewerPlays => "出牌 -1".into(),
+            BossModifier::NoDiscards => "不能弃牌".into(),
+            BossModifier::DebuffedSuit(suit) => format!("{}子不计分", suit.label()),
+        }
+    }
 }
 
+/// Plays and discards at the start of a blind, before stake and boss penalties
+const PLAYS_PER_BLIND: u32 = 4;
+const DISCARDS_PER_BLIND: u32 = 4;
+
+/// Clearing the boss of this level wins the run
+pub const DEFAULT_FINAL_LEVEL: u32 = 8;
+
+/// Endless-mode targets are multiplied by this for every level past the final one
+const ENDLESS_GROWTH: u32 = 2;
+
 #[derive(Resource)]
 pub struct GameState {
     pub level: u32,
+    pub final_level: u32,
+    /// Keep playing past `final_level` with exponentially growing targets
+    pub endless: bool,
     pub sub_round: SubRound,
+    pub stake: Stake,
     pub plays_remaining: u32,
     pub discards_remaining: u32,
-    pub target_score: u32,
-    pub current_score: u32,
-    pub base_ante: u32,
-    pub multiplier: u32,
+    pub target_score: Score,
+    pub current_score: Score,
+    pub base_ante: Score,
+    pub multiplier: Score,
     pub hand_size: usize,
+    pub money: u32,
+    pub play_mode: PlayMode,
+    pub ruleset: RulesetKind,
+    /// Plays and discards made so far this sub-round
+    pub plays_made: u32,
+    pub discards_made: u32,
+    /// Plays on the board when riichi was declared this sub-round
+    pub riichi: Option<u32>,
+    /// Sichuan only: the suit declared void this sub-round (定缺)
+    pub void_suit: Option<TileSuit>,
+    /// Sichuan only: 换三张 has been used this sub-round
+    pub exchanged: bool,
+    pub boss_modifier: Option<BossModifier>,
 }
 
 impl Default for GameState {
     fn default() -> Self {
         Self {
             level: 1,
+            final_level: DEFAULT_FINAL_LEVEL,
+            endless: false,
             sub_round: SubRound::SmallBlind,
+            stake: Stake::White,
             plays_remaining: 4,
             discards_remaining: 4,
-            target_score: 100,
-            current_score: 0,
-            base_ante: 10,
-            multiplier: 1,
+            target_score: Score::from(100u32),
+            current_score: Score::ZERO,
+            base_ante: Score::from(10u32),
+            multiplier: Score::from(1u32),
             hand_size: 14,
+            money: 0,
+            play_mode: PlayMode::Free,
+            ruleset: RulesetKind::Riichi,
+            plays_made: 0,
+            discards_made: 0,
+            riichi: None,
+            void_suit: None,
+            exchanged: false,
+            boss_modifier: None,
         }
     }
 }
 
 impl GameState {
+    pub fn new(stake: Stake) -> Self {
+        let mut state = Self {
+            stake,
+            ..default()
+        };
+        state.reset_for_sub_round();
+        state
+    }
+
     pub fn reset_for_new_game(&mut self) {
-        *self = Self::default();
+        *self = Self::new(self.stake);
     }
 
     pub fn reset_for_sub_round(&mut self) {
-        self.plays_remaining = 4;
-        self.discards_remaining = 4;
-        self.current_score = 0;
-        self.multiplier = 1;
-        self.target_score = self.sub_round.target_multiplier() * self.level;
+        let boss_rules = match self.sub_round {
+            SubRound::SmallBlind => false,
+            SubRound::BigBlind => self.stake.boss_modifier_on_big_blind(),
+            SubRound::Boss => true,
+        };
+        self.boss_modifier = boss_rules.then(BossModifier::roll);
+        self.plays_remaining = self.plays_per_blind();
+        self.discards_remaining = self.discards_per_blind();
+        self.current_score = Score::ZERO;
+        self.multiplier = Score::from(1u32);
+        self.plays_made = 0;
+        self.discards_made = 0;
+        self.riichi = None;
+        self.void_suit = None;
+        self.exchanged = false;
+        self.target_score = Score::from(self.sub_round.target_multiplier()) * self.level_factor();
+    }
+
+    pub fn plays_per_blind(&self) -> u32 {
+        match self.boss_modifier {
+            Some(BossModifier::FewerPlays) => PLAYS_PER_BLIND - 1,
+            _ => PLAYS_PER_BLIND,
+        }
     }
 
+    pub fn discards_per_blind(&self) -> u32 {
+        match self.boss_modifier {
+            Some(BossModifier::NoDiscards) => 0,
+            _ => DISCARDS_PER_BLIND - self.stake.discard_penalty(),
+        }
+    }
+
+    /// The suit this blind's boss modifier stops from scoring
+    pub fn debuffed_suit(&self) -> Option<TileSuit> {
+        match self.boss_modifier {
+            Some(BossModifier::DebuffedSuit(suit)) => Some(suit),
+            _ => None,
+        }
+    }
+
+    /// Sichuan blinds open with 换三张 and 定缺; nothing is played until a suit is void
+    pub fn awaiting_void(&self) -> bool {
+        self.ruleset == RulesetKind::Sichuan && self.void_suit.is_none()
+    }
+
+    /// Target scaling for the current level.
+    /// Grows linearly (or faster at steep stakes) up to `final_level`,
+    /// then exponentially in endless mode.
+    fn level_factor(&self) -> Score {
+        let scaled = |level: u32| {
+            if self.stake.steep_targets() {
+                level * (level + 1) / 2
+            } else {
+                level
+            }
+        };
+        if self.level <= self.final_level {
+            return scaled(self.level).into();
+        }
+        let extra = self.level - self.final_level;
+        Score::from(scaled(self.final_level)) * Score::from(ENDLESS_GROWTH).pow(extra)
+    }
+
+    /// 场风: 东 at level 1, turning one wind per level
+    pub fn round_wind(&self) -> TileId {
+        TileId {
+            suit: TileSuit::Wind,
+            value: ((self.level - 1) % 4) as u8 + 1,
+        }
+    }
+
+    /// 自风: starts on the round wind and turns with each sub-round
+    pub fn seat_wind(&self) -> TileId {
+        TileId {
+            suit: TileSuit::Wind,
+            value: ((self.level - 1 + self.sub_round as u32) % 4) as u8 + 1,
+        }
+    }
+
+    pub fn is_final_boss(&self) -> bool {
+        self.sub_round == SubRound::Boss && self.level == self.final_level
+    }
+
+    /// Money paid out for clearing the current blind at the current stake
+    pub fn blind_reward(&self) -> u32 {
+        if self.sub_round == SubRound::SmallBlind && self.stake.no_small_blind_reward() {
+            0
+        } else {
+            self.sub_round.reward()
+        }
+    }
+
+    /// Move on to the next blind. Returns `false` when the final boss has
+    /// just been cleared outside endless mode, i.e. the run is won.
     pub fn advance_sub_round(&mut self) -> bool {
+        if self.is_final_boss() && !self.endless {
+            return false;
+        }
         if let Some(next) = self.sub_round.next() {
             self.sub_round = next;
-            true
         } else {
             // Passed boss → advance level
             self.level += 1;
             self.sub_round = SubRound::SmallBlind;
-            true
         }
+        true
+    }
+
+    /// Continue a won run into endless mode, starting at the level after the final boss
+    pub fn enter_endless(&mut self) {
+        self.endless = true;
+        self.advance_sub_round();
+        self.reset_for_sub_round();
     }
 }
 
 #[derive(Resource, Default)]
 pub struct TileWall {
     pub tiles: Vec<Entity>,
+    /// The final tile drawn, once the wall has run out (海底)
+    pub last_tile: Option<Entity>,
+}
+
+impl TileWall {
+    pub fn draw(&mut self) -> Option<Entity> {
+        let entity = self.tiles.pop()?;
+        if self.tiles.is_empty() {
+            self.last_tile = Some(entity);
+        }
+        Some(entity)
+    }
+
+    pub fn clear(&mut self) {
+        self.tiles.clear();
+        self.last_tile = None;
+    }
 }
 
 #[derive(Resource, Default)]
@@ -98,7 +412,466 @@ pub struct PlayerHand {
     pub tiles: Vec<Entity>,
 }
 
+/// A meld declared by a single play, with its tile entities
+#[derive(Debug, Clone)]
+pub struct DeclaredMeld {
+    pub meld: Meld,
+    pub tiles: Vec<Entity>,
+}
+
 #[derive(Resource, Default)]
 pub struct PlayBoard {
     pub tiles: Vec<Entity>,
+    /// Declared melds in play order, or the winning hand's melds once it's scored
+    pub melds: Vec<DeclaredMeld>,
+    /// Flowers and seasons revealed this sub-round (补花)
+    pub bonus: Vec<Entity>,
+}
+
+impl PlayBoard {
+    pub fn clear(&mut self) {
+        self.tiles.clear();
+        self.melds.clear();
+        self.bonus.clear();
+    }
+
+    /// Tiles counting toward a full hand; a kong's fourth tile doesn't
+    pub fn counted_tiles(&self) -> usize {
+        let kongs = self.melds.iter().filter(|d| d.meld.kind == MeldKind::Kong).count();
+        self.tiles.len() - kongs
+    }
+}
+
+/// How plays go onto the board, picked on the menu for the next run
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PlayMode {
+    /// Any selection can be played; the hand is only read at scoring
+    #[default]
+    Free,
+    /// Each play must declare one meld: a sequence, triplet, kong or the pair
+    Declared,
+}
+
+impl PlayMode {
+    pub fn label(&self) -> &'static str {
+        match self {
+            PlayMode::Free => "自由出牌",
+            PlayMode::Declared => "报组出牌",
+        }
+    }
+
+    pub fn toggle(&self) -> Self {
+        match self {
+            PlayMode::Free => PlayMode::Declared,
+            PlayMode::Declared => PlayMode::Free,
+        }
+    }
+}
+
+/// How many levels a run lasts, picked on the menu for the next run
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RunLength {
+    Short,
+    #[default]
+    Standard,
+    Long,
+}
+
+impl RunLength {
+    /// The level whose boss wins the run
+    pub fn final_level(&self) -> u32 {
+        match self {
+            RunLength::Short => 4,
+            RunLength::Standard => DEFAULT_FINAL_LEVEL,
+            RunLength::Long => 12,
+        }
+    }
+
+    pub fn label(&self) -> String {
+        format!("通关 {} 关", self.final_level())
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            RunLength::Short => RunLength::Standard,
+            RunLength::Standard => RunLength::Long,
+            RunLength::Long => RunLength::Short,
+        }
+    }
+}
+
+/// How the hand is ordered on screen
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortMode {
+    /// By suit, then value
+    #[default]
+    Suit,
+    /// By value, then suit
+    Value,
+    /// Left as arranged by dragging; new tiles go on the right
+    Manual,
+}
+
+impl SortMode {
+    pub fn label(&self) -> &'static str {
+        match self {
+            SortMode::Suit => "按花色",
+            SortMode::Value => "按点数",
+            SortMode::Manual => "手动",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            SortMode::Suit => SortMode::Value,
+            SortMode::Value => SortMode::Manual,
+            SortMode::Manual => SortMode::Suit,
+        }
+    }
+
+    pub fn prev(&self) -> Self {
+        self.next().next()
+    }
+
+    /// Sort key of a tile in the hand; honors go after the number tiles by value.
+    /// `None` in manual mode, where the hand isn't sorted.
+    pub fn key(&self, id: TileId) -> Option<(u8, u8, u8)> {
+        match self {
+            SortMode::Suit => Some((id.suit as u8, id.value, 0)),
+            SortMode::Value => Some((id.is_honor() as u8, id.value, id.suit as u8)),
+            SortMode::Manual => None,
+        }
+    }
+
+    /// Order hand tiles for display. Manual mode keeps the arranged order,
+    /// with tiles drawn since left where they were added.
+    pub fn arrange<T>(&self, tiles: &mut [T], id: impl Fn(&T) -> Option<TileId>) {
+        if *self != SortMode::Manual {
+            tiles.sort_by_key(|tile| id(tile).and_then(|id| self.key(id)));
+        }
+    }
+}
+
+/// Playback speed of the score breakdown, cycled from the game UI
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ScoreAnimationSpeed {
+    #[default]
+    Normal,
+    Fast,
+    Faster,
+}
+
+impl ScoreAnimationSpeed {
+    pub fn multiplier(&self) -> f32 {
+        match self {
+            ScoreAnimationSpeed::Normal => 1.0,
+            ScoreAnimationSpeed::Fast => 2.0,
+            ScoreAnimationSpeed::Faster => 4.0,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ScoreAnimationSpeed::Normal => "速度 ×1",
+            ScoreAnimationSpeed::Fast => "速度 ×2",
+            ScoreAnimationSpeed::Faster => "速度 ×4",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            ScoreAnimationSpeed::Normal => ScoreAnimationSpeed::Fast,
+            ScoreAnimationSpeed::Fast => ScoreAnimationSpeed::Faster,
+            ScoreAnimationSpeed::Faster => ScoreAnimationSpeed::Normal,
+        }
+    }
+}
+
+/// Most jokers a run can hold at once
+pub const JOKER_SLOTS: usize = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Joker {
+    pub kind: JokerKind,
+    /// 永恒: can't be sold
+    pub eternal: bool,
+}
+
+impl Joker {
+    pub fn label(&self) -> String {
+        if self.eternal {
+            format!("{} (永恒)", self.kind.label())
+        } else {
+            self.kind.label().into()
+        }
+    }
+}
+
+/// Joker slots; each joker joins the scoring pipeline in slot order
+#[derive(Resource, Debug, Default)]
+pub struct Jokers {
+    pub slots: Vec<Joker>,
+}
+
+impl Jokers {
+    pub fn modifiers(&self) -> Vec<&dyn ScoreModifier> {
+        self.slots.iter().map(|joker| &joker.kind as &dyn ScoreModifier).collect()
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.slots.len() >= JOKER_SLOTS
+    }
+}
+
+/// Per-run pattern levels bought with pattern cards; every pattern starts at Lv.1
+#[derive(Resource, Debug, Default)]
+pub struct PatternLevels {
+    levels: HashMap<HandPattern, u32>,
+}
+
+impl PatternLevels {
+    pub fn level(&self, pattern: HandPattern) -> u32 {
+        self.levels.get(&pattern).copied().unwrap_or(1)
+    }
+
+    pub fn level_up(&mut self, pattern: HandPattern) {
+        if pattern.is_upgradable() {
+            *self.levels.entry(pattern).or_insert(1) += 1;
+        }
+    }
+}
+
+const PROFILE_PATH: &str = "profile.txt";
+
+/// Persistent player profile, kept on disk between runs
+#[derive(Resource, Debug, Default, PartialEq, Eq)]
+pub struct Profile {
+    /// Highest stake the player may pick
+    pub unlocked_stake: Stake,
+    /// Stake chosen on the menu for the next run
+    pub selected_stake: Stake,
+}
+
+impl Profile {
+    pub fn load() -> Self {
+        fs::read_to_string(PROFILE_PATH)
+            .map(|contents| Self::parse(&contents))
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        if let Err(err) = fs::write(PROFILE_PATH, self.serialize()) {
+            warn!("failed to save profile to {}: {}", PROFILE_PATH, err);
+        }
+    }
+
+    /// Parse `key=value` lines; unknown keys and bad values are ignored
+    fn parse(contents: &str) -> Self {
+        let mut profile = Self::default();
+        for line in contents.lines() {
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            let Some(stake) = value.trim().parse().ok().and_then(Stake::from_index) else {
+                continue;
+            };
+            match key.trim() {
+                "unlocked_stake" => profile.unlocked_stake = stake,
+                "selected_stake" => profile.selected_stake = stake,
+                _ => {}
+            }
+        }
+        profile.selected_stake = profile.selected_stake.min(profile.unlocked_stake);
+        profile
+    }
+
+    fn serialize(&self) -> String {
+        format!(
+            "unlocked_stake={}\nselected_stake={}\n",
+            self.unlocked_stake.index(),
+            self.selected_stake.index()
+        )
+    }
+
+    /// Select a stake for the next run; locked stakes are rejected
+    pub fn select_stake(&mut self, stake: Stake) -> bool {
+        if stake > self.unlocked_stake {
+            return false;
+        }
+        self.selected_stake = stake;
+        true
+    }
+
+    /// Winning a run unlocks the stake above the one it was played at
+    pub fn record_win(&mut self, stake: Stake) {
+        if let Some(next) = stake.next() {
+            if next > self.unlocked_stake {
+                self.unlocked_stake = next;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stake_penalties_stack() {
+        let white = GameState::new(Stake::White);
+        assert_eq!(white.discards_remaining, 4);
+        assert_eq!(white.blind_reward(), 3);
+        assert!(Stake::White.penalties().is_empty());
+
+        let blue = GameState::new(Stake::Blue);
+        assert_eq!(blue.discards_remaining, 3);
+        assert_eq!(blue.blind_reward(), 0);
+        assert_eq!(Stake::Blue.penalties().len(), 4);
+    }
+
+    #[test]
+    fn test_boss_modifiers() {
+        let blind = |stake, sub_round| {
+            let mut gs = GameState {
+                sub_round,
+                ..GameState::new(stake)
+            };
+            gs.reset_for_sub_round();
+            gs.boss_modifier
+        };
+        assert!(blind(Stake::White, SubRound::Boss).is_some());
+        assert!(blind(Stake::Blue, SubRound::BigBlind).is_none());
+        assert!(blind(Stake::Gold, SubRound::BigBlind).is_some());
+        assert!(blind(Stake::Gold, SubRound::SmallBlind).is_none());
+
+        let mut gs = GameState::new(Stake::Blue);
+        gs.boss_modifier = Some(BossModifier::FewerPlays);
+        assert_eq!((gs.plays_per_blind(), gs.discards_per_blind()), (3, 3));
+        gs.boss_modifier = Some(BossModifier::NoDiscards);
+        assert_eq!((gs.plays_per_blind(), gs.discards_per_blind()), (4, 0));
+        gs.boss_modifier = Some(BossModifier::DebuffedSuit(TileSuit::Pin));
+        assert_eq!(gs.debuffed_suit(), Some(TileSuit::Pin));
+        assert_eq!(Stake::Gold.penalties().len(), 5);
+    }
+
+    #[test]
+    fn test_steep_targets() {
+        let mut white = GameState::new(Stake::White);
+        let mut green = GameState::new(Stake::Green);
+        assert_eq!(white.target_score, green.target_score);
+
+        white.level = 3;
+        green.level = 3;
+        white.reset_for_sub_round();
+        green.reset_for_sub_round();
+        assert_eq!(white.target_score, 300);
+        assert_eq!(green.target_score, 600);
+    }
+
+    #[test]
+    fn test_final_boss_wins_run() {
+        let mut gs = GameState::new(Stake::White);
+        gs.level = gs.final_level;
+        gs.sub_round = SubRound::Boss;
+        assert!(!gs.advance_sub_round());
+        assert_eq!(gs.level, gs.final_level);
+
+        gs.enter_endless();
+        assert_eq!(gs.level, gs.final_level + 1);
+        assert_eq!(gs.sub_round, SubRound::SmallBlind);
+        assert!(gs.advance_sub_round());
+    }
+
+    #[test]
+    fn test_endless_targets_grow_exponentially() {
+        let mut gs = GameState::new(Stake::White);
+        gs.endless = true;
+        gs.level = gs.final_level;
+        gs.reset_for_sub_round();
+        let at_final = gs.target_score;
+
+        gs.level += 2;
+        gs.reset_for_sub_round();
+        assert_eq!(gs.target_score, at_final * Score::from(4u32));
+
+        // Far past u32 range without overflowing
+        gs.level += 100;
+        gs.reset_for_sub_round();
+        assert_eq!(gs.target_score, at_final * Score::from(2u32).pow(102));
+        assert!(gs.target_score.magnitude() > 30);
+    }
+
+    #[test]
+    fn test_winds_turn_with_level_and_sub_round() {
+        let mut gs = GameState::new(Stake::White);
+        assert_eq!(gs.round_wind().label(), "东");
+        assert_eq!(gs.seat_wind(), gs.round_wind());
+
+        gs.sub_round = SubRound::Boss;
+        assert_eq!(gs.seat_wind().label(), "西");
+
+        gs.level = 4;
+        assert_eq!(gs.round_wind().label(), "北");
+        assert_eq!(gs.seat_wind().label(), "南");
+    }
+
+    #[test]
+    fn test_profile_roundtrip_and_unlock() {
+        let mut profile = Profile::default();
+        assert!(!profile.select_stake(Stake::Red));
+
+        profile.record_win(Stake::White);
+        assert_eq!(profile.unlocked_stake, Stake::Red);
+        assert!(profile.select_stake(Stake::Red));
+
+        // Winning a lower stake again never re-locks anything
+        profile.record_win(Stake::White);
+        assert_eq!(profile.unlocked_stake, Stake::Red);
+
+        assert_eq!(Profile::parse(&profile.serialize()), profile);
+        assert_eq!(Profile::parse("selected_stake=5\ngarbage"), Profile::default());
+    }
+
+    #[test]
+    fn test_sort_modes() {
+        let tile = |suit, value| TileId { suit, value };
+        let mut ids = vec![
+            tile(TileSuit::Wind, 1),
+            tile(TileSuit::Pin, 2),
+            tile(TileSuit::Man, 3),
+            tile(TileSuit::Man, 2),
+        ];
+        assert_eq!(SortMode::Manual.key(ids[0]), None);
+        ids.sort_by_key(|&id| SortMode::Suit.key(id));
+        assert_eq!(ids[..2], [tile(TileSuit::Man, 2), tile(TileSuit::Man, 3)]);
+
+        ids.sort_by_key(|&id| SortMode::Value.key(id));
+        assert_eq!(
+            ids,
+            [
+                tile(TileSuit::Man, 2),
+                tile(TileSuit::Pin, 2),
+                tile(TileSuit::Man, 3),
+                tile(TileSuit::Wind, 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_manual_order_kept() {
+        let tile = |suit, value| TileId { suit, value };
+        // Arranged by dragging, then a 1万 drawn on the right
+        let arranged = vec![
+            tile(TileSuit::Wind, 1),
+            tile(TileSuit::Man, 9),
+            tile(TileSuit::Pin, 2),
+            tile(TileSuit::Man, 1),
+        ];
+        let mut ids = arranged.clone();
+        SortMode::Manual.arrange(&mut ids, |&id| Some(id));
+        assert_eq!(ids, arranged);
+
+        SortMode::Suit.arrange(&mut ids, |&id| Some(id));
+        assert_eq!(ids[0], tile(TileSuit::Man, 1));
+    }
 }
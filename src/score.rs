@@ -0,0 +1,182 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, AddAssign, Mul, MulAssign};
+
+/// Largest mantissa kept before the exponent starts counting.
+/// Below this every integer score is exact.
+const MANTISSA_LIMIT: f64 = 1e15;
+
+/// Scores at or above this are shown in scientific notation
+const DISPLAY_SCI_THRESHOLD: f64 = 1e9;
+
+/// Non-negative score value that doesn't overflow as jokers and ×mult stack.
+///
+/// Stored as `mantissa × 10^exponent`. While the value fits below
+/// `MANTISSA_LIMIT` the exponent is 0 and the mantissa is the exact value;
+/// past that the mantissa is kept in `[MANTISSA_LIMIT / 10, MANTISSA_LIMIT)`,
+/// so every value has exactly one representation and comparisons are cheap.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Score {
+    mantissa: f64,
+    exponent: i64,
+}
+
+impl Score {
+    pub const ZERO: Score = Score {
+        mantissa: 0.0,
+        exponent: 0,
+    };
+
+    fn normalized(mut mantissa: f64, mut exponent: i64) -> Self {
+        if !mantissa.is_finite() || mantissa < 0.0 {
+            mantissa = 0.0;
+            exponent = 0;
+        }
+        while mantissa >= MANTISSA_LIMIT {
+            mantissa /= 10.0;
+            exponent += 1;
+        }
+        while exponent > 0 && mantissa < MANTISSA_LIMIT / 10.0 {
+            mantissa *= 10.0;
+            exponent -= 1;
+        }
+        if exponent == 0 {
+            // Small values are whole numbers; drop float drift from the shifts above
+            mantissa = mantissa.round();
+        }
+        Self { mantissa, exponent }
+    }
+
+    /// Power of ten of the leading digit, e.g. 2 for 123
+    pub fn magnitude(&self) -> i64 {
+        if self.mantissa == 0.0 {
+            return 0;
+        }
+        self.mantissa.log10().floor() as i64 + self.exponent
+    }
+
+    /// `self^exp`, for exponential target growth
+    pub fn pow(self, exp: u32) -> Self {
+        (0..exp).fold(Score::from(1u32), |acc, _| acc * self)
+    }
+}
+
+impl From<u32> for Score {
+    fn from(value: u32) -> Self {
+        Self::normalized(value as f64, 0)
+    }
+}
+
+impl From<u64> for Score {
+    fn from(value: u64) -> Self {
+        Self::normalized(value as f64, 0)
+    }
+}
+
+impl Add for Score {
+    type Output = Score;
+
+    fn add(self, rhs: Score) -> Score {
+        let (big, small) = if self >= rhs { (self, rhs) } else { (rhs, self) };
+        let shift = big.exponent - small.exponent;
+        if shift > 30 {
+            return big;
+        }
+        Score::normalized(big.mantissa + small.mantissa / 10f64.powi(shift as i32), big.exponent)
+    }
+}
+
+impl AddAssign for Score {
+    fn add_assign(&mut self, rhs: Score) {
+        *self = *self + rhs;
+    }
+}
+
+impl Mul for Score {
+    type Output = Score;
+
+    fn mul(self, rhs: Score) -> Score {
+        Score::normalized(self.mantissa * rhs.mantissa, self.exponent + rhs.exponent)
+    }
+}
+
+impl MulAssign for Score {
+    fn mul_assign(&mut self, rhs: Score) {
+        *self = *self * rhs;
+    }
+}
+
+impl PartialOrd for Score {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(
+            self.exponent
+                .cmp(&other.exponent)
+                .then(self.mantissa.total_cmp(&other.mantissa)),
+        )
+    }
+}
+
+impl PartialEq<u32> for Score {
+    fn eq(&self, other: &u32) -> bool {
+        *self == Score::from(*other)
+    }
+}
+
+impl PartialOrd<u32> for Score {
+    fn partial_cmp(&self, other: &u32) -> Option<Ordering> {
+        self.partial_cmp(&Score::from(*other))
+    }
+}
+
+/// Whole numbers up to a billion, then `1.23e15` style
+impl fmt::Display for Score {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.exponent == 0 && self.mantissa < DISPLAY_SCI_THRESHOLD {
+            return write!(f, "{}", self.mantissa as u64);
+        }
+        let mut magnitude = self.magnitude();
+        let shift = magnitude - self.exponent;
+        let mut lead = (self.mantissa / 10f64.powi(shift as i32) * 100.0).round() / 100.0;
+        if lead >= 10.0 {
+            lead /= 10.0;
+            magnitude += 1;
+        }
+        write!(f, "{:.2}e{}", lead, magnitude)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_small_values_are_exact() {
+        let chips = Score::from(10u32) + Score::from(64u32);
+        assert_eq!(chips, 74);
+        assert_eq!(chips * Score::from(4u32), 296);
+        assert_eq!(format!("{}", chips), "74");
+        assert!(Score::from(100u32) > Score::from(99u32));
+    }
+
+    #[test]
+    fn test_large_values_dont_overflow() {
+        let huge = Score::from(u32::MAX) * Score::from(u32::MAX) * Score::from(u32::MAX);
+        assert!(huge > Score::from(u64::MAX));
+        assert_eq!(huge.magnitude(), 28);
+        assert_eq!(format!("{}", huge), "7.92e28");
+
+        let doubled = Score::from(2u32).pow(1000);
+        assert_eq!(doubled.magnitude(), 301);
+        assert!(doubled * doubled > doubled);
+
+        // Adding something tiny to something huge changes nothing
+        assert_eq!(doubled + Score::from(1u32), doubled);
+    }
+
+    #[test]
+    fn test_display_switches_to_scientific() {
+        assert_eq!(format!("{}", Score::from(999_999_999u32)), "999999999");
+        assert_eq!(format!("{}", Score::from(1_230_000_000_000_000u64)), "1.23e15");
+        assert_eq!(format!("{}", Score::from(9_999_000_000u64)), "1.00e10");
+    }
+}